@@ -0,0 +1,193 @@
+use std::{any::TypeId, collections::HashSet, time::Duration};
+
+use crate::engine::{
+    ecs::{
+        component::Component,
+        event::Event,
+        reflect::{Reflect, TypeRegistry},
+        resource::Resource,
+        system::{IntoSystemDescriptor, Schedule, Stage},
+        world::World,
+    },
+    game::{
+        camera_controller::CameraControllerPlugin,
+        game_loop::{FixedTime, StarryGameLoop},
+        game_object::TransformComponent,
+        global_transform::{add_global_transforms, propagate_transforms, GlobalTransform},
+    },
+    input::plugin::InputPlugin,
+    rendering::renderer::RenderPlugin,
+    resources::assets::AssetPlugin,
+    time::Time,
+    window::WindowPlugin,
+};
+
+pub trait Plugin: 'static {
+    fn build(&self, app: &mut App);
+}
+
+type StartupSystem = Box<dyn FnOnce(&mut World)>;
+type Runner = Box<dyn FnOnce(App)>;
+
+pub struct App {
+    world: World,
+    game_loop: StarryGameLoop,
+    startup_systems: Vec<StartupSystem>,
+    runner: Option<Runner>,
+    plugins: HashSet<TypeId>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self {
+            world: World::new(),
+            game_loop: StarryGameLoop::new(Schedule::new()),
+            startup_systems: Vec::new(),
+            runner: None,
+            plugins: HashSet::new(),
+        }
+    }
+
+    pub fn add_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        if !self.plugins.insert(TypeId::of::<P>()) {
+            panic!("Plugin {} was already added.", std::any::type_name::<P>());
+        }
+
+        plugin.build(self);
+        self
+    }
+
+    pub fn is_plugin_added<P: Plugin>(&self) -> bool {
+        self.plugins.contains(&TypeId::of::<P>())
+    }
+
+    pub fn add_system<Marker>(
+        &mut self,
+        stage: Stage,
+        system: impl IntoSystemDescriptor<Marker>,
+    ) -> &mut Self {
+        self.game_loop.get_schedule_mut().add_system(stage, system);
+        self
+    }
+
+    pub fn add_startup_system(&mut self, system: impl FnOnce(&mut World) + 'static) -> &mut Self {
+        self.startup_systems.push(Box::new(system));
+        self
+    }
+
+    pub fn insert_resource<T: Resource>(&mut self, resource: T) -> &mut Self {
+        self.world.insert_resource(resource);
+        self
+    }
+
+    pub fn init_resource<T: Resource + Default>(&mut self) -> &mut Self {
+        self.world.init_resource::<T>();
+        self
+    }
+
+    pub fn add_event<T: Event>(&mut self) -> &mut Self {
+        self.world.add_event::<T>();
+        self
+    }
+
+    pub fn register_component<T: Component + Reflect + Default>(
+        &mut self,
+        name: &'static str,
+    ) -> &mut Self {
+        self.world.init_resource::<TypeRegistry>();
+        self.world
+            .resource_mut::<TypeRegistry>()
+            .register_component::<T>(name);
+        self
+    }
+
+    pub fn register_resource<T: Resource + Reflect>(&mut self, name: &'static str) -> &mut Self {
+        self.world.init_resource::<TypeRegistry>();
+        self.world
+            .resource_mut::<TypeRegistry>()
+            .register_resource::<T>(name);
+        self
+    }
+
+    pub fn set_runner(&mut self, runner: impl FnOnce(App) + 'static) -> &mut Self {
+        self.runner = Some(Box::new(runner));
+        self
+    }
+
+    pub fn get_world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn get_world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn get_game_loop_mut(&mut self) -> &mut StarryGameLoop {
+        &mut self.game_loop
+    }
+
+    pub fn startup(&mut self) {
+        for system in std::mem::take(&mut self.startup_systems) {
+            system(&mut self.world);
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.startup();
+        self.game_loop.run_frame(&mut self.world);
+    }
+
+    pub fn update_with_delta(&mut self, delta: Duration) {
+        self.startup();
+        self.world.init_resource::<Time>();
+        self.world.resource_mut::<Time>().advance(delta);
+        self.game_loop.run_frame_with_delta(&mut self.world, delta);
+    }
+
+    pub fn run(&mut self) {
+        let mut app = std::mem::take(self);
+
+        match app.runner.take() {
+            Some(runner) => runner(app),
+            None => app.update(),
+        }
+    }
+}
+
+pub struct CorePlugin;
+
+impl Plugin for CorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Time>()
+            .init_resource::<FixedTime>()
+            .register_component::<TransformComponent>("transform")
+            .register_component::<GlobalTransform>("global_transform")
+            .add_system(Stage::PreUpdate, add_global_transforms)
+            .add_system(
+                Stage::PostUpdate,
+                propagate_transforms.label("propagate_transforms"),
+            );
+    }
+}
+
+pub struct DefaultPlugins;
+
+impl Plugin for DefaultPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(CorePlugin)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(InputPlugin::default())
+            .add_plugin(RenderPlugin)
+            .add_plugin(CameraControllerPlugin)
+            .add_plugin(AssetPlugin);
+
+        #[cfg(feature = "gilrs")]
+        app.add_plugin(crate::engine::input::gilrs_backend::GilrsPlugin);
+    }
+}
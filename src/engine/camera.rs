@@ -1,15 +1,50 @@
-use cgmath::{Matrix4, SquareMatrix, Deg, Angle, Zero, Vector3, prelude::InnerSpace};
+use cgmath::{Matrix4, SquareMatrix, Deg, Angle, Zero, Vector2, Vector3, Vector4, Quaternion, Rotation, prelude::InnerSpace};
+
+use vulkano::pipeline::graphics::depth_stencil::CompareOp;
+
+use crate::{engine::{ecs::resource::Resource, frustum::{Frustum, Ray}}, impl_reflect_struct};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepthMode {
+    #[default]
+    Standard,
+    ReverseZ,
+    InfiniteReverseZ,
+}
+
+impl DepthMode {
+    pub fn is_reversed(&self) -> bool {
+        !matches!(self, Self::Standard)
+    }
+
+    pub fn get_clear_depth(&self) -> f32 {
+        if self.is_reversed() { 0.0 } else { 1.0 }
+    }
+
+    pub fn get_compare_op(&self) -> CompareOp {
+        if self.is_reversed() { CompareOp::Greater } else { CompareOp::Less }
+    }
+}
 
 pub struct StarryCamera {
     projection_matrix: Matrix4<f32>,
-    view_matrix: Matrix4<f32>
+    view_matrix: Matrix4<f32>,
+    depth_mode: DepthMode
 }
 
+impl Resource for StarryCamera {}
+
+impl_reflect_struct!(StarryCamera {
+    projection_matrix: Matrix4<f32>,
+    view_matrix: Matrix4<f32>,
+});
+
 impl StarryCamera {
     pub fn new() -> Self {
         Self { 
             projection_matrix: Matrix4::zero(),
             view_matrix: Matrix4::identity(), 
+            depth_mode: DepthMode::Standard,
         }
     }
 
@@ -29,6 +64,7 @@ impl StarryCamera {
         self.projection_matrix[3][0] = -(right + left) / (right - left);
         self.projection_matrix[3][1] = -(bottom + top) / (bottom - top);
         self.projection_matrix[3][2] = -near / (far - near);
+        self.depth_mode = DepthMode::Standard;
     }
 
     pub fn set_perspective_projection(
@@ -37,17 +73,55 @@ impl StarryCamera {
         aspect: f32,
         near: f32,
         far: f32
+    ) {
+        self.set_perspective_fov(fov_y, aspect);
+        self.projection_matrix[2][2] = far / (far - near);
+        self.projection_matrix[3][2] = -(far * near) / (far - near);
+        self.depth_mode = DepthMode::Standard;
+    }
+
+    pub fn set_reverse_z_perspective_projection(
+        &mut self,
+        fov_y: f32,
+        aspect: f32,
+        near: f32,
+        far: f32
+    ) {
+        self.set_perspective_fov(fov_y, aspect);
+        self.projection_matrix[2][2] = -near / (far - near);
+        self.projection_matrix[3][2] = (far * near) / (far - near);
+        self.depth_mode = DepthMode::ReverseZ;
+    }
+
+    pub fn set_infinite_reverse_z_perspective_projection(
+        &mut self,
+        fov_y: f32,
+        aspect: f32,
+        near: f32
+    ) {
+        self.set_perspective_fov(fov_y, aspect);
+        self.projection_matrix[3][2] = near;
+        self.depth_mode = DepthMode::InfiniteReverseZ;
+    }
+
+    fn set_perspective_fov(
+        &mut self,
+        fov_y: f32,
+        aspect: f32
     ) {
         if fov_y >= 180.0 {
             panic!("FOV too large!");
         }
 
         let tan_half_fov_y = Deg(fov_y / 2.0).tan();
+        self.projection_matrix = Matrix4::zero();
         self.projection_matrix[0][0] = 1.0 / (aspect * tan_half_fov_y);
         self.projection_matrix[1][1] = 1.0 / (tan_half_fov_y);
-        self.projection_matrix[2][2] = far / (far - near);
         self.projection_matrix[2][3] = 1.0;
-        self.projection_matrix[3][2] = -(far * near) / (far - near);
+    }
+
+    pub fn get_depth_mode(&self) -> DepthMode {
+        self.depth_mode
     }
 
     pub fn set_view_direction(
@@ -125,6 +199,30 @@ impl StarryCamera {
         self.view_matrix[3][2] = -w.dot(position);
     }
 
+    pub fn set_view_rotation(
+        &mut self,
+        position: Vector3<f32>,
+        rotation: Quaternion<f32>
+    ) {
+        let u = rotation.rotate_vector(Vector3::unit_x());
+        let v = rotation.rotate_vector(Vector3::unit_y());
+        let w = rotation.rotate_vector(Vector3::unit_z());
+
+        self.view_matrix = Matrix4::identity();
+        self.view_matrix[0][0] = u.x;
+        self.view_matrix[1][0] = u.y;
+        self.view_matrix[2][0] = u.z;
+        self.view_matrix[0][1] = v.x;
+        self.view_matrix[1][1] = v.y;
+        self.view_matrix[2][1] = v.z;
+        self.view_matrix[0][2] = w.x;
+        self.view_matrix[1][2] = w.y;
+        self.view_matrix[2][2] = w.z;
+        self.view_matrix[3][0] = -u.dot(position);
+        self.view_matrix[3][1] = -v.dot(position);
+        self.view_matrix[3][2] = -w.dot(position);
+    }
+
     pub fn get_projection_matrix(&self) -> Matrix4<f32> {
         self.projection_matrix
     }
@@ -132,4 +230,214 @@ impl StarryCamera {
     pub fn get_view_matrix(&self) -> Matrix4<f32> {
         self.view_matrix
     }
-}
\ No newline at end of file
+
+    pub fn get_frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.projection_matrix * self.view_matrix)
+    }
+
+    // vulkan ndc is y-down like the engine, so pixels map to ndc without a flip
+    pub fn screen_to_world(
+        &self,
+        screen_position: Vector2<f32>,
+        screen_size: Vector2<f32>,
+        depth: f32
+    ) -> Option<Vector3<f32>> {
+        if screen_size.x <= 0.0 || screen_size.y <= 0.0 {
+            return None;
+        }
+
+        let inverse = (self.projection_matrix * self.view_matrix).invert()?;
+
+        let ndc = Vector4::new(
+            2.0 * screen_position.x / screen_size.x - 1.0,
+            2.0 * screen_position.y / screen_size.y - 1.0,
+            depth,
+            1.0
+        );
+
+        let world = inverse * ndc;
+        if world.w.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        Some(world.truncate() / world.w)
+    }
+
+    // the second point sits halfway into the depth range so infinite projections stay finite
+    pub fn screen_to_world_ray(
+        &self,
+        screen_position: Vector2<f32>,
+        screen_size: Vector2<f32>
+    ) -> Option<Ray> {
+        let near_depth = if self.depth_mode.is_reversed() { 1.0 } else { 0.0 };
+        let origin = self.screen_to_world(screen_position, screen_size, near_depth)?;
+        let target = self.screen_to_world(screen_position, screen_size, 0.5)?;
+
+        Some(Ray::new(origin, target - origin))
+    }
+
+    pub fn world_to_screen(
+        &self,
+        position: Vector3<f32>,
+        screen_size: Vector2<f32>
+    ) -> Option<Vector3<f32>> {
+        let clip = self.projection_matrix * self.view_matrix * position.extend(1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+
+        let ndc = clip.truncate() / clip.w;
+        Some(Vector3::new(
+            (ndc.x + 1.0) * 0.5 * screen_size.x,
+            (ndc.y + 1.0) * 0.5 * screen_size.y,
+            ndc.z
+        ))
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        let translation = self.view_matrix.w.truncate();
+        -(self.get_right() * translation.x + -self.get_up() * translation.y + self.get_forward() * translation.z)
+    }
+
+    pub fn get_forward(&self) -> Vector3<f32> {
+        Vector3 {
+            x: self.view_matrix[0][2],
+            y: self.view_matrix[1][2],
+            z: self.view_matrix[2][2],
+        }
+    }
+
+    pub fn get_right(&self) -> Vector3<f32> {
+        Vector3 {
+            x: self.view_matrix[0][0],
+            y: self.view_matrix[1][0],
+            z: self.view_matrix[2][0],
+        }
+    }
+
+    pub fn get_up(&self) -> Vector3<f32> {
+        -Vector3 {
+            x: self.view_matrix[0][1],
+            y: self.view_matrix[1][1],
+            z: self.view_matrix[2][1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{One, Rotation3};
+
+    use super::*;
+    use crate::engine::frustum::BoundingSphere;
+
+    fn is_close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 1e-3
+    }
+
+    #[test]
+    fn screen_rays_hit_projected_points() {
+        let size = Vector2::new(800.0, 600.0);
+        let position = Vector3::new(1.0, -2.0, -3.0);
+        let rotation = Quaternion::from_angle_y(Deg(30.0)) * Quaternion::from_angle_x(Deg(10.0));
+        let mut camera = StarryCamera::new();
+
+        for depth_mode in [
+            DepthMode::Standard,
+            DepthMode::ReverseZ,
+            DepthMode::InfiniteReverseZ,
+        ] {
+            match depth_mode {
+                DepthMode::Standard => {
+                    camera.set_perspective_projection(60.0, 800.0 / 600.0, 0.1, 100.0)
+                }
+                DepthMode::ReverseZ => {
+                    camera.set_reverse_z_perspective_projection(60.0, 800.0 / 600.0, 0.1, 100.0)
+                }
+                DepthMode::InfiniteReverseZ => {
+                    camera.set_infinite_reverse_z_perspective_projection(60.0, 800.0 / 600.0, 0.1)
+                }
+            }
+            camera.set_view_rotation(position, rotation);
+
+            let center = camera.screen_to_world_ray(size / 2.0, size).unwrap();
+            assert!(
+                is_close(center.direction, camera.get_forward()),
+                "{depth_mode:?}"
+            );
+
+            let point = position + camera.get_forward() * 10.0 + camera.get_right() * 1.5;
+            let screen = camera.world_to_screen(point, size).unwrap();
+            let ray = camera.screen_to_world_ray(screen.truncate(), size).unwrap();
+            let distance = (point - ray.origin).dot(ray.direction);
+            assert!(is_close(ray.get_point(distance), point), "{depth_mode:?}");
+        }
+    }
+
+    #[test]
+    fn degenerate_projections_have_no_screen_rays() {
+        let size = Vector2::new(800.0, 600.0);
+        let mut camera = StarryCamera::new();
+        assert!(camera.screen_to_world(size / 2.0, size, 0.5).is_none());
+        assert!(camera.screen_to_world_ray(size / 2.0, size).is_none());
+
+        camera.set_perspective_projection(60.0, 800.0 / 600.0, 0.1, 100.0);
+        assert!(camera
+            .screen_to_world_ray(size / 2.0, Vector2::new(0.0, 0.0))
+            .is_none());
+        assert!(camera.screen_to_world_ray(size / 2.0, size).is_some());
+    }
+
+    fn get_depth(camera: &StarryCamera, z: f32) -> f32 {
+        let clip = camera.get_projection_matrix() * Vector4::new(0.0, 0.0, z, 1.0);
+        clip.z / clip.w
+    }
+
+    #[test]
+    fn reverse_z_maps_near_to_one_and_far_to_zero() {
+        let mut camera = StarryCamera::new();
+        camera.set_perspective_projection(90.0, 1.0, 0.1, 100.0);
+        assert_eq!(camera.get_depth_mode(), DepthMode::Standard);
+        assert!(get_depth(&camera, 0.1).abs() < 1e-5);
+        assert!((get_depth(&camera, 100.0) - 1.0).abs() < 1e-5);
+        assert_eq!(camera.get_depth_mode().get_clear_depth(), 1.0);
+        assert_eq!(camera.get_depth_mode().get_compare_op(), CompareOp::Less);
+
+        camera.set_reverse_z_perspective_projection(90.0, 1.0, 0.1, 100.0);
+        assert_eq!(camera.get_depth_mode(), DepthMode::ReverseZ);
+        assert!((get_depth(&camera, 0.1) - 1.0).abs() < 1e-5);
+        assert!(get_depth(&camera, 100.0).abs() < 1e-5);
+        assert!(get_depth(&camera, 1.0) > get_depth(&camera, 2.0));
+        assert_eq!(camera.get_depth_mode().get_clear_depth(), 0.0);
+        assert_eq!(camera.get_depth_mode().get_compare_op(), CompareOp::Greater);
+
+        camera.set_infinite_reverse_z_perspective_projection(90.0, 1.0, 0.1);
+        assert_eq!(camera.get_depth_mode(), DepthMode::InfiniteReverseZ);
+        assert!((get_depth(&camera, 0.1) - 1.0).abs() < 1e-5);
+        assert!(get_depth(&camera, 1e7) > 0.0 && get_depth(&camera, 1e7) < 1e-6);
+    }
+
+    #[test]
+    fn reverse_z_frustums_cull_behind_and_beside_the_camera() {
+        let visible = BoundingSphere::new(Vector3::new(0.0, 0.0, 5.0), 0.5);
+        let behind = BoundingSphere::new(Vector3::new(0.0, 0.0, -0.7), 0.5);
+        let beside = BoundingSphere::new(Vector3::new(8.0, 0.0, 5.0), 0.5);
+        let distant = BoundingSphere::new(Vector3::new(0.0, 0.0, 5000.0), 0.5);
+        let mut camera = StarryCamera::new();
+
+        camera.set_reverse_z_perspective_projection(90.0, 1.0, 0.1, 100.0);
+        camera.set_view_rotation(Vector3::new(0.0, 0.0, 0.0), Quaternion::one());
+        let frustum = camera.get_frustum();
+        assert!(frustum.intersects_sphere(&visible));
+        assert!(!frustum.intersects_sphere(&behind));
+        assert!(!frustum.intersects_sphere(&beside));
+        assert!(!frustum.intersects_sphere(&distant));
+
+        camera.set_infinite_reverse_z_perspective_projection(90.0, 1.0, 0.1);
+        let frustum = camera.get_frustum();
+        assert!(frustum.intersects_sphere(&visible));
+        assert!(!frustum.intersects_sphere(&behind));
+        assert!(!frustum.intersects_sphere(&beside));
+        assert!(frustum.intersects_sphere(&distant));
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::{BTreeMap, BTreeSet},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AccessKind {
+    Component,
+    Resource,
+}
+
+type AccessKey = (AccessKind, TypeId);
+
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    reads: BTreeMap<AccessKey, &'static str>,
+    writes: BTreeMap<AccessKey, &'static str>,
+    invalid: BTreeSet<&'static str>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_read<T: 'static>(&mut self) {
+        self.insert_read::<T>(AccessKind::Component);
+    }
+
+    pub fn add_write<T: 'static>(&mut self) {
+        self.insert_write::<T>(AccessKind::Component);
+    }
+
+    // a filter only inspects the storage, which a write from the same query already covers
+    pub fn add_filter_read<T: 'static>(&mut self) {
+        if !self.has_write::<T>() {
+            self.add_read::<T>();
+        }
+    }
+
+    pub fn add_resource_read<T: 'static>(&mut self) {
+        self.insert_read::<T>(AccessKind::Resource);
+    }
+
+    pub fn add_resource_write<T: 'static>(&mut self) {
+        self.insert_write::<T>(AccessKind::Resource);
+    }
+
+    pub fn has_read<T: 'static>(&self) -> bool {
+        self.reads
+            .contains_key(&(AccessKind::Component, TypeId::of::<T>()))
+    }
+
+    pub fn has_write<T: 'static>(&self) -> bool {
+        self.writes
+            .contains_key(&(AccessKind::Component, TypeId::of::<T>()))
+    }
+
+    pub fn has_resource_read<T: 'static>(&self) -> bool {
+        self.reads
+            .contains_key(&(AccessKind::Resource, TypeId::of::<T>()))
+    }
+
+    pub fn has_resource_write<T: 'static>(&self) -> bool {
+        self.writes
+            .contains_key(&(AccessKind::Resource, TypeId::of::<T>()))
+    }
+
+    pub fn extend(&mut self, other: &Access) {
+        for (key, name) in &other.reads {
+            self.insert_read_key(*key, name);
+        }
+
+        for (key, name) in &other.writes {
+            self.insert_write_key(*key, name);
+        }
+
+        self.invalid.extend(other.invalid.iter().copied());
+    }
+
+    pub fn get_invalid(&self) -> Vec<&'static str> {
+        self.invalid.iter().copied().collect()
+    }
+
+    pub fn is_compatible(&self, other: &Access) -> bool {
+        self.get_conflicts(other).is_empty()
+    }
+
+    pub fn get_conflicts(&self, other: &Access) -> Vec<&'static str> {
+        let mut conflicts = self
+            .writes
+            .iter()
+            .filter(|(key, _)| other.reads.contains_key(key) || other.writes.contains_key(key))
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+
+        conflicts.extend(
+            self.reads
+                .iter()
+                .filter(|(key, _)| other.writes.contains_key(key))
+                .map(|(_, name)| *name),
+        );
+
+        conflicts
+    }
+
+    fn insert_read<T: 'static>(&mut self, kind: AccessKind) {
+        self.insert_read_key((kind, TypeId::of::<T>()), type_name::<T>());
+    }
+
+    fn insert_write<T: 'static>(&mut self, kind: AccessKind) {
+        self.insert_write_key((kind, TypeId::of::<T>()), type_name::<T>());
+    }
+
+    fn insert_read_key(&mut self, key: AccessKey, name: &'static str) {
+        if self.writes.contains_key(&key) {
+            self.invalid.insert(name);
+        }
+
+        self.reads.insert(key, name);
+    }
+
+    fn insert_write_key(&mut self, key: AccessKey, name: &'static str) {
+        if self.reads.contains_key(&key) || self.writes.contains_key(&key) {
+            self.invalid.insert(name);
+        }
+
+        self.writes.insert(key, name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Health;
+
+    struct Position;
+
+    #[test]
+    fn reads_share_and_writes_conflict() {
+        let mut reader = Access::new();
+        reader.add_read::<Health>();
+        reader.add_resource_write::<Position>();
+
+        let mut other_reader = Access::new();
+        other_reader.add_read::<Health>();
+        other_reader.add_write::<Position>();
+        assert!(reader.is_compatible(&other_reader));
+
+        let mut writer = Access::new();
+        writer.add_write::<Health>();
+        assert_eq!(reader.get_conflicts(&writer), vec![type_name::<Health>()]);
+        assert_eq!(writer.get_conflicts(&reader), vec![type_name::<Health>()]);
+    }
+
+    #[test]
+    fn aliasing_within_one_access_is_invalid() {
+        let mut access = Access::new();
+        access.add_read::<Health>();
+        access.add_resource_read::<Health>();
+        access.add_resource_write::<Position>();
+        assert!(access.get_invalid().is_empty());
+
+        access.add_write::<Health>();
+        assert_eq!(access.get_invalid(), vec![type_name::<Health>()]);
+    }
+}
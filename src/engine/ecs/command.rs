@@ -0,0 +1,263 @@
+use super::{
+    component::{Bundle, Component},
+    entity::Entity,
+    event::Event,
+    observer::Lifecycle,
+    resource::Resource,
+    world::World,
+};
+
+type Command = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+#[derive(Default)]
+pub struct CommandQueue {
+    commands: Vec<Command>,
+}
+
+impl CommandQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: impl FnOnce(&mut World) + Send + Sync + 'static) {
+        self.commands.push(Box::new(command));
+    }
+
+    pub fn apply(&mut self, world: &mut World) {
+        world.flush_entities();
+
+        for command in self.commands.drain(..) {
+            command(world);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+pub struct Commands<'w, 's> {
+    queue: &'s mut CommandQueue,
+    world: &'w World,
+}
+
+impl<'w, 's> Commands<'w, 's> {
+    pub fn new(queue: &'s mut CommandQueue, world: &'w World) -> Self {
+        Self { queue, world }
+    }
+
+    pub fn spawn_empty<'a>(&'a mut self) -> EntityCommands<'w, 's, 'a> {
+        let entity = self.world.reserve_entity();
+        self.entity(entity)
+    }
+
+    pub fn spawn<'a>(&'a mut self, bundle: impl Bundle) -> EntityCommands<'w, 's, 'a> {
+        let mut entity = self.spawn_empty();
+        entity.insert(bundle);
+        entity
+    }
+
+    pub fn entity<'a>(&'a mut self, entity: Entity) -> EntityCommands<'w, 's, 'a> {
+        EntityCommands {
+            entity,
+            commands: self,
+        }
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.add(move |world| {
+            world.despawn(entity);
+        });
+    }
+
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        self.add(move |world| {
+            world.despawn_recursive(entity);
+        });
+    }
+
+    pub fn trigger<E: Event>(&mut self, entity: Entity, event: E) {
+        self.add(move |world| {
+            if world.is_alive(entity) {
+                world.trigger(entity, event);
+            }
+        });
+    }
+
+    pub fn insert_resource<T: Resource>(&mut self, resource: T) {
+        self.add(move |world| {
+            world.insert_resource(resource);
+        });
+    }
+
+    pub fn remove_resource<T: Resource>(&mut self) {
+        self.add(move |world| {
+            world.remove_resource::<T>();
+        });
+    }
+
+    pub fn add(&mut self, command: impl FnOnce(&mut World) + Send + Sync + 'static) {
+        self.queue.push(command);
+    }
+}
+
+pub struct EntityCommands<'w, 's, 'a> {
+    entity: Entity,
+    commands: &'a mut Commands<'w, 's>,
+}
+
+impl EntityCommands<'_, '_, '_> {
+    pub fn get_id(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn insert(&mut self, bundle: impl Bundle) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world| {
+            if world.is_alive(entity) {
+                world.insert_bundle(entity, bundle);
+            }
+        });
+        self
+    }
+
+    pub fn remove<T: Component>(&mut self) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world| {
+            world.remove::<T>(entity);
+        });
+        self
+    }
+
+    pub fn add_child(&mut self, child: Entity) -> &mut Self {
+        let parent = self.entity;
+        self.commands.add(move |world| world.add_child(parent, child));
+        self
+    }
+
+    pub fn observe<T: Component>(
+        &mut self,
+        lifecycle: Lifecycle,
+        observer: impl FnMut(&mut World, Entity) + Send + Sync + 'static,
+    ) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world| {
+            if world.is_alive(entity) {
+                world.observe::<T>(entity, lifecycle, observer);
+            }
+        });
+        self
+    }
+
+    pub fn observe_event<E: Event>(
+        &mut self,
+        observer: impl FnMut(&mut World, Entity, &E) + Send + Sync + 'static,
+    ) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world| {
+            if world.is_alive(entity) {
+                world.observe_event::<E>(entity, observer);
+            }
+        });
+        self
+    }
+
+    pub fn remove_parent(&mut self) -> &mut Self {
+        let child = self.entity;
+        self.commands.add(move |world| {
+            world.remove_parent(child);
+        });
+        self
+    }
+
+    pub fn despawn(&mut self) {
+        self.commands.despawn(self.entity);
+    }
+
+    pub fn despawn_recursive(&mut self) {
+        self.commands.despawn_recursive(self.entity);
+    }
+
+    pub fn add(&mut self, command: impl FnOnce(Entity, &mut World) + Send + Sync + 'static) -> &mut Self {
+        let entity = self.entity;
+        self.commands.add(move |world| command(entity, world));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ecs::{
+        query::Query,
+        system::{Schedule, Stage},
+    };
+
+    struct Spawner;
+
+    impl Component for Spawner {}
+
+    #[derive(Debug, PartialEq)]
+    struct Spawned(u32);
+
+    impl Component for Spawned {}
+
+    fn spawn_from_spawners(mut commands: Commands, spawners: Query<(Entity, &Spawner)>) {
+        for (entity, _) in spawners.iter() {
+            commands.spawn(Spawned(1));
+            commands
+                .entity(entity)
+                .insert(Spawned(7))
+                .remove::<Spawner>();
+        }
+    }
+
+    fn despawn_spawned(mut commands: Commands, spawned: Query<(Entity, &Spawned)>) {
+        for (entity, spawned) in spawned.iter() {
+            if spawned.0 == 1 {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    #[test]
+    fn commands_apply_after_the_stage() {
+        let mut world = World::new();
+        let spawner = world.spawn(Spawner);
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, spawn_from_spawners);
+        schedule.run(&mut world);
+
+        assert_eq!(world.len(), 2);
+        assert!(!world.has::<Spawner>(spawner));
+        assert_eq!(*world.get::<Spawned>(spawner).unwrap(), Spawned(7));
+        assert_eq!(world.query::<&Spawned>().count(), 2);
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, despawn_spawned);
+        schedule.run(&mut world);
+
+        assert_eq!(world.len(), 1);
+        assert_eq!(world.query::<&Spawned>().count(), 1);
+    }
+
+    #[test]
+    fn reserved_entities_wait_for_the_queue() {
+        let mut world = World::new();
+        let mut queue = CommandQueue::new();
+
+        let entity = Commands::new(&mut queue, &world).spawn(Spawned(3)).get_id();
+        assert!(!world.is_alive(entity));
+        assert_eq!(queue.len(), 1);
+
+        queue.apply(&mut world);
+        assert!(queue.is_empty());
+        assert!(world.is_alive(entity));
+        assert_eq!(*world.get::<Spawned>(entity).unwrap(), Spawned(3));
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    any::Any,
+    ops::{Deref, DerefMut},
+    sync::{RwLock, RwLockReadGuard},
+};
+
+use super::{entity::Entity, world::World};
+
+pub trait Component: Send + Sync + 'static {}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Tick(u32);
+
+impl Tick {
+    pub const fn new(tick: u32) -> Self {
+        Self(tick)
+    }
+
+    pub const fn get(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_newer_than(&self, last_run: Tick, this_run: Tick) -> bool {
+        let ticks_since_change = this_run.0.wrapping_sub(self.0);
+        let ticks_since_system = this_run.0.wrapping_sub(last_run.0);
+
+        ticks_since_system > ticks_since_change
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemTicks {
+    pub last_run: Tick,
+    pub this_run: Tick,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComponentTicks {
+    pub added: Tick,
+    pub changed: Tick,
+}
+
+impl ComponentTicks {
+    pub fn new(tick: Tick) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    pub fn is_added(&self, ticks: SystemTicks) -> bool {
+        self.added.is_newer_than(ticks.last_run, ticks.this_run)
+    }
+
+    pub fn is_changed(&self, ticks: SystemTicks) -> bool {
+        self.changed.is_newer_than(ticks.last_run, ticks.this_run)
+    }
+}
+
+pub struct ComponentStorage<T: Component> {
+    dense: Vec<T>,
+    ticks: Vec<ComponentTicks>,
+    entities: Vec<Entity>,
+    sparse: Vec<Option<usize>>,
+}
+
+impl<T: Component> ComponentStorage<T> {
+    pub fn new() -> Self {
+        Self {
+            dense: Vec::new(),
+            ticks: Vec::new(),
+            entities: Vec::new(),
+            sparse: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, component: T, tick: Tick) -> Option<T> {
+        let sparse_index = entity.get_index() as usize;
+
+        if let Some(dense_index) = self.sparse.get(sparse_index).copied().flatten() {
+            if self.entities[dense_index] == entity {
+                self.ticks[dense_index].changed = tick;
+            } else {
+                self.entities[dense_index] = entity;
+                self.ticks[dense_index] = ComponentTicks::new(tick);
+            }
+
+            return Some(std::mem::replace(&mut self.dense[dense_index], component));
+        }
+
+        if sparse_index >= self.sparse.len() {
+            self.sparse.resize(sparse_index + 1, None);
+        }
+
+        self.sparse[sparse_index] = Some(self.dense.len());
+        self.dense.push(component);
+        self.ticks.push(ComponentTicks::new(tick));
+        self.entities.push(entity);
+        None
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let dense_index = self.get_dense_index(entity)?;
+
+        self.sparse[entity.get_index() as usize] = None;
+        self.entities.swap_remove(dense_index);
+        self.ticks.swap_remove(dense_index);
+        let component = self.dense.swap_remove(dense_index);
+
+        if let Some(moved) = self.entities.get(dense_index) {
+            self.sparse[moved.get_index() as usize] = Some(dense_index);
+        }
+
+        Some(component)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.get_dense_index(entity).map(|index| &self.dense[index])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.get_dense_index(entity)
+            .map(move |index| &mut self.dense[index])
+    }
+
+    pub fn get_with_ticks_mut(&mut self, entity: Entity) -> Option<(&mut T, &mut ComponentTicks)> {
+        self.get_dense_index(entity)
+            .map(move |index| (&mut self.dense[index], &mut self.ticks[index]))
+    }
+
+    pub fn get_ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.get_dense_index(entity).map(|index| self.ticks[index])
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get_dense_index(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    pub fn get_entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(self.dense.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entities.iter().copied().zip(self.dense.iter_mut())
+    }
+
+    pub(crate) fn get_dense_index(&self, entity: Entity) -> Option<usize> {
+        self.sparse
+            .get(entity.get_index() as usize)
+            .copied()
+            .flatten()
+            .filter(|index| self.entities[*index] == entity)
+    }
+
+    pub(crate) fn get_by_dense_index(&self, index: usize) -> &T {
+        &self.dense[index]
+    }
+
+    pub(crate) fn get_dense_ptr(&mut self) -> *mut T {
+        self.dense.as_mut_ptr()
+    }
+
+    pub(crate) fn get_ticks_ptr(&mut self) -> *mut ComponentTicks {
+        self.ticks.as_mut_ptr()
+    }
+}
+
+impl<T: Component> Default for ComponentStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) trait ErasedStorage: Send + Sync {
+    fn remove_entity(&mut self, entity: Entity);
+
+    fn contains_entity(&self, entity: Entity) -> bool;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Component> ErasedStorage for RwLock<ComponentStorage<T>> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.get_mut().unwrap().remove(entity);
+    }
+
+    fn contains_entity(&self, entity: Entity) -> bool {
+        self.read().unwrap().contains(entity)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub struct ComponentRef<'a, T: Component> {
+    guard: RwLockReadGuard<'a, ComponentStorage<T>>,
+    index: usize,
+}
+
+impl<'a, T: Component> ComponentRef<'a, T> {
+    pub(crate) fn new(guard: RwLockReadGuard<'a, ComponentStorage<T>>, index: usize) -> Self {
+        Self { guard, index }
+    }
+}
+
+impl<T: Component> Deref for ComponentRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.get_by_dense_index(self.index)
+    }
+}
+
+pub struct Mut<'a, T: Component> {
+    value: &'a mut T,
+    ticks: &'a mut ComponentTicks,
+    system_ticks: SystemTicks,
+}
+
+impl<'a, T: Component> Mut<'a, T> {
+    pub(crate) fn new(
+        value: &'a mut T,
+        ticks: &'a mut ComponentTicks,
+        system_ticks: SystemTicks,
+    ) -> Self {
+        Self {
+            value,
+            ticks,
+            system_ticks,
+        }
+    }
+
+    pub fn is_added(&self) -> bool {
+        self.ticks.is_added(self.system_ticks)
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.ticks.is_changed(self.system_ticks)
+    }
+
+    pub fn set_changed(&mut self) {
+        self.ticks.changed = self.system_ticks.this_run;
+    }
+
+    pub fn bypass_change_detection(&mut self) -> &mut T {
+        self.value
+    }
+
+    pub fn into_inner(self) -> &'a mut T {
+        self.ticks.changed = self.system_ticks.this_run;
+        self.value
+    }
+}
+
+impl<T: Component> Deref for Mut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: Component> DerefMut for Mut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.set_changed();
+        self.value
+    }
+}
+
+pub trait Bundle: Send + Sync + 'static {
+    fn insert_into(self, world: &mut World, entity: Entity);
+}
+
+impl<T: Component> Bundle for T {
+    fn insert_into(self, world: &mut World, entity: Entity) {
+        world.insert(entity, self);
+    }
+}
+
+macro_rules! impl_bundle_for_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: Component),*> Bundle for ($($name,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            fn insert_into(self, world: &mut World, entity: Entity) {
+                let ($($name,)*) = self;
+                $(world.insert(entity, $name);)*
+            }
+        }
+    };
+}
+
+impl_bundle_for_tuple!();
+impl_bundle_for_tuple!(A);
+impl_bundle_for_tuple!(A, B);
+impl_bundle_for_tuple!(A, B, C);
+impl_bundle_for_tuple!(A, B, C, D);
+impl_bundle_for_tuple!(A, B, C, D, E);
+impl_bundle_for_tuple!(A, B, C, D, E, F);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G);
+impl_bundle_for_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Value(usize);
+
+    impl Component for Value {}
+
+    #[test]
+    fn removal_swaps_the_last_component_into_place() {
+        let entities = (0..3)
+            .map(|index| Entity::new(index, 0))
+            .collect::<Vec<_>>();
+        let mut storage = ComponentStorage::new();
+        for (value, entity) in entities.iter().enumerate() {
+            storage.insert(*entity, Value(value), Tick::new(1));
+        }
+
+        assert_eq!(storage.remove(entities[0]), Some(Value(0)));
+        assert_eq!(storage.remove(entities[0]), None);
+        assert_eq!(storage.get_entities(), &[entities[2], entities[1]]);
+        assert_eq!(storage.get(entities[2]), Some(&Value(2)));
+        assert_eq!(storage.get(entities[1]), Some(&Value(1)));
+
+        assert_eq!(storage.remove(entities[2]), Some(Value(2)));
+        assert_eq!(storage.get_entities(), &[entities[1]]);
+        assert_eq!(storage.get(entities[1]), Some(&Value(1)));
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[test]
+    fn stale_generations_do_not_alias() {
+        let old = Entity::new(0, 0);
+        let new = Entity::new(0, 1);
+        let mut storage = ComponentStorage::new();
+        storage.insert(old, Value(1), Tick::new(1));
+
+        assert_eq!(storage.get(new), None);
+        assert_eq!(storage.remove(new), None);
+
+        assert_eq!(storage.insert(new, Value(2), Tick::new(2)), Some(Value(1)));
+        assert_eq!(storage.get(old), None);
+        assert_eq!(storage.get(new), Some(&Value(2)));
+        assert_eq!(
+            storage.get_ticks(new),
+            Some(ComponentTicks::new(Tick::new(2)))
+        );
+    }
+
+    #[test]
+    fn ticks_compare_across_wraparound() {
+        let last_run = Tick::new(u32::MAX - 1);
+        let this_run = Tick::new(3);
+
+        assert!(Tick::new(u32::MAX).is_newer_than(last_run, this_run));
+        assert!(Tick::new(1).is_newer_than(last_run, this_run));
+        assert!(!Tick::new(u32::MAX - 1).is_newer_than(last_run, this_run));
+        assert!(!Tick::new(u32::MAX - 5).is_newer_than(last_run, this_run));
+    }
+}
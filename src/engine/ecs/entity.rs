@@ -1 +1,205 @@
-// pub type Entity(u32);
\ No newline at end of file
+use std::sync::atomic::{AtomicI64, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub const fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    pub const fn get_index(&self) -> u32 {
+        self.index
+    }
+
+    pub const fn get_generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Default)]
+pub struct EntityAllocator {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free_list: Vec<u32>,
+    free_cursor: AtomicI64,
+    alive_count: usize,
+}
+
+impl EntityAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        self.flush();
+        self.alive_count += 1;
+
+        let entity = match self.free_list.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity::new(index, self.generations[index as usize])
+            }
+
+            None => {
+                let index = self.generations.len() as u32;
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity::new(index, 0)
+            }
+        };
+
+        self.reset_free_cursor();
+        entity
+    }
+
+    pub fn reserve(&self) -> Entity {
+        let cursor = self.free_cursor.fetch_sub(1, Ordering::AcqRel);
+
+        if cursor > 0 {
+            let index = self.free_list[cursor as usize - 1];
+            Entity::new(index, self.generations[index as usize])
+        } else {
+            let index = self.generations.len() as i64 - cursor;
+            Entity::new(index as u32, 0)
+        }
+    }
+
+    pub fn flush(&mut self) {
+        let cursor = *self.free_cursor.get_mut();
+
+        if cursor >= self.free_list.len() as i64 {
+            return;
+        }
+
+        let reused = self.free_list.split_off(cursor.max(0) as usize);
+        for index in &reused {
+            self.alive[*index as usize] = true;
+        }
+        self.alive_count += reused.len();
+
+        if cursor < 0 {
+            let fresh = (-cursor) as usize;
+            self.generations.resize(self.generations.len() + fresh, 0);
+            self.alive.resize(self.alive.len() + fresh, true);
+            self.alive_count += fresh;
+        }
+
+        self.reset_free_cursor();
+    }
+
+    pub fn spawn_batch(&mut self, count: usize) -> Vec<Entity> {
+        let reused = count.min(self.free_list.len());
+        self.generations.reserve(count - reused);
+        self.alive.reserve(count - reused);
+
+        (0..count).map(|_| self.spawn()).collect()
+    }
+
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        self.flush();
+
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free_list.push(entity.index);
+        self.alive_count -= 1;
+        self.reset_free_cursor();
+        true
+    }
+
+    pub fn despawn_batch(&mut self, entities: &[Entity]) -> usize {
+        entities
+            .iter()
+            .filter(|entity| self.despawn(**entity))
+            .count()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.generations.len()
+            && self.alive[index]
+            && self.generations[index] == entity.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.alive_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alive_count == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(index, _)| Entity::new(index as u32, self.generations[index]))
+    }
+
+    fn reset_free_cursor(&mut self) {
+        *self.free_cursor.get_mut() = self.free_list.len() as i64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, thread};
+
+    use super::*;
+
+    #[test]
+    fn despawned_indices_are_reused_with_a_new_generation() {
+        let mut allocator = EntityAllocator::new();
+        let entities = allocator.spawn_batch(3);
+        assert!(allocator.despawn(entities[1]));
+        assert!(!allocator.despawn(entities[1]));
+
+        let reused = allocator.spawn();
+        assert_eq!(reused.get_index(), 1);
+        assert_eq!(reused.get_generation(), 1);
+        assert!(!allocator.is_alive(entities[1]));
+        assert!(allocator.is_alive(reused));
+        assert_eq!(allocator.len(), 3);
+
+        assert_eq!(allocator.despawn_batch(&entities), 2);
+        assert_eq!(allocator.iter().collect::<Vec<_>>(), vec![reused]);
+    }
+
+    #[test]
+    fn concurrent_reservations_are_unique_and_flushed() {
+        let mut allocator = EntityAllocator::new();
+        let entities = allocator.spawn_batch(8);
+        allocator.despawn_batch(&entities[..4]);
+
+        let reserved = thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| scope.spawn(|| (0..4).map(|_| allocator.reserve()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(reserved.iter().collect::<HashSet<_>>().len(), 16);
+        assert!(reserved.iter().all(|entity| !allocator.is_alive(*entity)));
+
+        allocator.flush();
+        assert!(reserved.iter().all(|entity| allocator.is_alive(*entity)));
+        assert!(entities[..4]
+            .iter()
+            .all(|entity| !allocator.is_alive(*entity)));
+        assert_eq!(allocator.len(), 20);
+        assert_eq!(allocator.spawn().get_index(), 20);
+    }
+}
@@ -0,0 +1,190 @@
+use std::mem;
+
+use super::{
+    resource::{Res, ResMut, Resource},
+    world::World,
+};
+
+pub trait Event: Send + Sync + 'static {}
+
+pub struct Events<T: Event> {
+    previous: Vec<(usize, T)>,
+    current: Vec<(usize, T)>,
+    event_count: usize,
+}
+
+impl<T: Event> Resource for Events<T> {}
+
+impl<T: Event> Default for Events<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Event> Events<T> {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            event_count: 0,
+        }
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.current.push((self.event_count, event));
+        self.event_count += 1;
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        for event in events {
+            self.send(event);
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.previous = mem::take(&mut self.current);
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.current.is_empty()
+    }
+
+    pub fn get_event_count(&self) -> usize {
+        self.event_count
+    }
+
+    pub fn iter_since(&self, cursor: usize) -> impl Iterator<Item = &T> {
+        self.previous
+            .iter()
+            .chain(self.current.iter())
+            .filter(move |(id, _)| *id >= cursor)
+            .map(|(_, event)| event)
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.previous
+            .drain(..)
+            .chain(self.current.drain(..))
+            .map(|(_, event)| event)
+    }
+}
+
+pub(crate) fn update_events<T: Event>(world: &World) {
+    world.resource_mut::<Events<T>>().update();
+}
+
+pub struct EventWriter<'w, T: Event> {
+    events: ResMut<'w, Events<T>>,
+}
+
+impl<'w, T: Event> EventWriter<'w, T> {
+    pub(crate) fn new(events: ResMut<'w, Events<T>>) -> Self {
+        Self { events }
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.events.send_batch(events);
+    }
+}
+
+pub struct EventReader<'w, 's, T: Event> {
+    events: Res<'w, Events<T>>,
+    cursor: &'s mut usize,
+}
+
+impl<'w, 's, T: Event> EventReader<'w, 's, T> {
+    pub(crate) fn new(events: Res<'w, Events<T>>, cursor: &'s mut usize) -> Self {
+        Self { events, cursor }
+    }
+
+    pub fn read(&mut self) -> impl Iterator<Item = &T> {
+        let cursor = *self.cursor;
+        *self.cursor = self.events.get_event_count();
+        self.events.iter_since(cursor)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.iter_since(*self.cursor).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self.cursor = self.events.get_event_count();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ecs::system::{Local, Schedule, Stage};
+
+    struct Ping(u32);
+
+    impl Event for Ping {}
+
+    #[derive(Default)]
+    struct Received(Vec<u32>);
+
+    impl Resource for Received {}
+
+    fn send_ping(mut pings: EventWriter<Ping>, mut frame: Local<u32>) {
+        *frame += 1;
+        pings.send(Ping(*frame));
+    }
+
+    fn receive_pings(mut pings: EventReader<Ping>, mut received: ResMut<Received>) {
+        received.0.extend(pings.read().map(|ping| ping.0));
+    }
+
+    #[test]
+    fn events_live_for_two_updates() {
+        let mut events = Events::new();
+        events.send(Ping(1));
+        events.update();
+        events.send(Ping(2));
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events.iter_since(1).map(|ping| ping.0).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        events.update();
+        assert_eq!(events.len(), 1);
+        events.update();
+        assert!(events.is_empty());
+        assert_eq!(events.get_event_count(), 2);
+    }
+
+    #[test]
+    fn readers_see_each_event_once() {
+        let mut world = World::new();
+        world.init_resource::<Received>();
+
+        // the reader runs first, so it only catches up on the next frame
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::PreUpdate, receive_pings);
+        schedule.add_system(Stage::Update, send_ping);
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+
+        assert_eq!(world.resource::<Received>().0, vec![1, 2]);
+        assert_eq!(world.resource::<Events<Ping>>().len(), 1);
+    }
+}
@@ -0,0 +1,49 @@
+use super::{component::Component, entity::Entity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(Entity);
+
+impl Component for Parent {}
+
+impl Parent {
+    pub(crate) fn new(parent: Entity) -> Self {
+        Self(parent)
+    }
+
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Children(Vec<Entity>);
+
+impl Component for Children {}
+
+impl Children {
+    pub(crate) fn push(&mut self, child: Entity) {
+        if !self.0.contains(&child) {
+            self.0.push(child);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, child: Entity) {
+        self.0.retain(|entity| *entity != child);
+    }
+
+    pub fn get(&self) -> &[Entity] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
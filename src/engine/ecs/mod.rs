@@ -1,3 +1,12 @@
 pub mod entity;
 pub mod component;
-pub mod system;
\ No newline at end of file
+pub mod system;
+pub mod world;
+pub mod query;
+pub mod access;
+pub mod resource;
+pub mod event;
+pub mod command;
+pub mod hierarchy;
+pub mod observer;
+pub mod reflect;
\ No newline at end of file
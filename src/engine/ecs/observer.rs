@@ -0,0 +1,90 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use super::{entity::Entity, world::World};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lifecycle {
+    Add,
+    Insert,
+    Remove,
+}
+
+pub type ComponentHook = fn(&mut World, Entity);
+
+#[derive(Default, Clone, Copy)]
+pub struct ComponentHooks {
+    on_add: Option<ComponentHook>,
+    on_insert: Option<ComponentHook>,
+    on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        Self::set_hook(&mut self.on_add, hook, "on_add");
+        self
+    }
+
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        Self::set_hook(&mut self.on_insert, hook, "on_insert");
+        self
+    }
+
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        Self::set_hook(&mut self.on_remove, hook, "on_remove");
+        self
+    }
+
+    pub fn get(&self, lifecycle: Lifecycle) -> Option<ComponentHook> {
+        match lifecycle {
+            Lifecycle::Add => self.on_add,
+            Lifecycle::Insert => self.on_insert,
+            Lifecycle::Remove => self.on_remove,
+        }
+    }
+
+    fn set_hook(slot: &mut Option<ComponentHook>, hook: ComponentHook, name: &str) {
+        if slot.is_some() {
+            panic!("Component hook {name} is already registered.");
+        }
+
+        *slot = Some(hook);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ObserverKey {
+    Component(TypeId, Lifecycle),
+    Event(TypeId),
+}
+
+pub(crate) type ObserverFn = Box<dyn FnMut(&mut World, Entity, &dyn Any) + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct Observers {
+    observers: HashMap<(Entity, ObserverKey), Vec<ObserverFn>>,
+}
+
+impl Observers {
+    pub(crate) fn add(&mut self, entity: Entity, key: ObserverKey, observer: ObserverFn) {
+        self.observers.entry((entity, key)).or_default().push(observer);
+    }
+
+    pub(crate) fn take(&mut self, entity: Entity, key: ObserverKey) -> Option<Vec<ObserverFn>> {
+        self.observers.remove(&(entity, key))
+    }
+
+    pub(crate) fn restore(&mut self, entity: Entity, key: ObserverKey, mut observers: Vec<ObserverFn>) {
+        if let Some(added) = self.observers.remove(&(entity, key)) {
+            observers.extend(added);
+        }
+
+        self.observers.insert((entity, key), observers);
+    }
+
+    pub(crate) fn remove_entity(&mut self, entity: Entity) {
+        self.observers.retain(|(observed, _), _| *observed != entity);
+    }
+}
@@ -0,0 +1,693 @@
+use std::{
+    marker::PhantomData,
+    ops::Deref,
+    slice,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+    vec,
+};
+
+use super::{
+    access::Access,
+    component::{Component, ComponentStorage, ComponentTicks, Mut, SystemTicks},
+    entity::Entity,
+    world::World,
+};
+
+pub trait QueryData {
+    type State<'w>;
+    type Item<'q>;
+
+    fn update_access(access: &mut Access);
+
+    fn borrow_state(world: &World, ticks: SystemTicks) -> Self::State<'_>;
+
+    fn get_candidates<'s>(state: &'s Self::State<'_>) -> Option<&'s [Entity]>;
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool;
+
+    /// # Safety
+    /// `entity` must match the state and must not be fetched again while the
+    /// returned item is alive.
+    unsafe fn fetch<'q>(state: &'q Self::State<'_>, entity: Entity) -> Self::Item<'q>;
+}
+
+/// # Safety
+/// Implementors must only hand out shared access to component data.
+pub unsafe trait ReadOnlyQueryData: QueryData {}
+
+pub trait QueryFilter {
+    type State<'w>;
+
+    fn update_access(access: &mut Access);
+
+    // `data_access` is the access of the query data the filter is paired with
+    fn borrow_state<'w>(
+        world: &'w World,
+        ticks: SystemTicks,
+        data_access: &Access,
+    ) -> Self::State<'w>;
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool;
+}
+
+pub struct WriteState<'w, T: Component> {
+    guard: RwLockWriteGuard<'w, ComponentStorage<T>>,
+    dense: *mut T,
+    component_ticks: *mut ComponentTicks,
+    ticks: SystemTicks,
+}
+
+fn borrow_read<T: Component>(world: &World) -> Option<RwLockReadGuard<'_, ComponentStorage<T>>> {
+    world.get_storage::<T>().map(|storage| {
+        storage.try_read().unwrap_or_else(|_| {
+            panic!(
+                "Component {} is already mutably borrowed.",
+                std::any::type_name::<T>()
+            )
+        })
+    })
+}
+
+// a filter on a component its own query writes cannot take a second lock, so it reads through
+// the storage that query holds exclusively for as long as both are alive
+pub enum FilterStorage<'w, T: Component> {
+    Locked(RwLockReadGuard<'w, ComponentStorage<T>>),
+    Shared(
+        *const ComponentStorage<T>,
+        PhantomData<&'w ComponentStorage<T>>,
+    ),
+}
+
+impl<T: Component> Deref for FilterStorage<'_, T> {
+    type Target = ComponentStorage<T>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Locked(guard) => guard,
+            Self::Shared(storage, _) => unsafe { &**storage },
+        }
+    }
+}
+
+fn borrow_filter<'w, T: Component>(
+    world: &'w World,
+    data_access: &Access,
+) -> Option<FilterStorage<'w, T>> {
+    let guard = borrow_read::<T>(world)?;
+
+    if data_access.has_write::<T>() {
+        let storage = &*guard as *const ComponentStorage<T>;
+        Some(FilterStorage::Shared(storage, PhantomData))
+    } else {
+        Some(FilterStorage::Locked(guard))
+    }
+}
+
+fn borrow_write<T: Component>(world: &World, ticks: SystemTicks) -> Option<WriteState<'_, T>> {
+    world.get_storage::<T>().map(|storage| {
+        let mut guard = storage.try_write().unwrap_or_else(|_| {
+            panic!(
+                "Component {} is already borrowed.",
+                std::any::type_name::<T>()
+            )
+        });
+        let dense = guard.get_dense_ptr();
+        let component_ticks = guard.get_ticks_ptr();
+
+        WriteState {
+            guard,
+            dense,
+            component_ticks,
+            ticks,
+        }
+    })
+}
+
+impl QueryData for Entity {
+    type State<'w> = ();
+    type Item<'q> = Entity;
+
+    fn update_access(_access: &mut Access) {}
+
+    fn borrow_state(_world: &World, _ticks: SystemTicks) -> Self::State<'_> {}
+
+    fn get_candidates<'s>(_state: &'s Self::State<'_>) -> Option<&'s [Entity]> {
+        None
+    }
+
+    fn matches(_state: &Self::State<'_>, _entity: Entity) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'q>(_state: &'q Self::State<'_>, entity: Entity) -> Self::Item<'q> {
+        entity
+    }
+}
+
+unsafe impl ReadOnlyQueryData for Entity {}
+
+impl<T: Component> QueryData for &T {
+    type State<'w> = Option<RwLockReadGuard<'w, ComponentStorage<T>>>;
+    type Item<'q> = &'q T;
+
+    fn update_access(access: &mut Access) {
+        access.add_read::<T>();
+    }
+
+    fn borrow_state(world: &World, _ticks: SystemTicks) -> Self::State<'_> {
+        borrow_read::<T>(world)
+    }
+
+    fn get_candidates<'s>(state: &'s Self::State<'_>) -> Option<&'s [Entity]> {
+        Some(state.as_ref().map_or(&[], |storage| storage.get_entities()))
+    }
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+        state
+            .as_ref()
+            .is_some_and(|storage| storage.contains(entity))
+    }
+
+    unsafe fn fetch<'q>(state: &'q Self::State<'_>, entity: Entity) -> Self::Item<'q> {
+        state.as_ref().unwrap().get(entity).unwrap()
+    }
+}
+
+unsafe impl<T: Component> ReadOnlyQueryData for &T {}
+
+impl<T: Component> QueryData for &mut T {
+    type State<'w> = Option<WriteState<'w, T>>;
+    type Item<'q> = Mut<'q, T>;
+
+    fn update_access(access: &mut Access) {
+        access.add_write::<T>();
+    }
+
+    fn borrow_state(world: &World, ticks: SystemTicks) -> Self::State<'_> {
+        borrow_write::<T>(world, ticks)
+    }
+
+    fn get_candidates<'s>(state: &'s Self::State<'_>) -> Option<&'s [Entity]> {
+        Some(state.as_ref().map_or(&[], |state| state.guard.get_entities()))
+    }
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+        state
+            .as_ref()
+            .is_some_and(|state| state.guard.contains(entity))
+    }
+
+    unsafe fn fetch<'q>(state: &'q Self::State<'_>, entity: Entity) -> Self::Item<'q> {
+        let state = state.as_ref().unwrap();
+        let index = state.guard.get_dense_index(entity).unwrap();
+
+        Mut::new(
+            &mut *state.dense.add(index),
+            &mut *state.component_ticks.add(index),
+            state.ticks,
+        )
+    }
+}
+
+impl<Q: QueryData> QueryData for Option<Q> {
+    type State<'w> = Q::State<'w>;
+    type Item<'q> = Option<Q::Item<'q>>;
+
+    fn update_access(access: &mut Access) {
+        Q::update_access(access);
+    }
+
+    fn borrow_state(world: &World, ticks: SystemTicks) -> Self::State<'_> {
+        Q::borrow_state(world, ticks)
+    }
+
+    fn get_candidates<'s>(_state: &'s Self::State<'_>) -> Option<&'s [Entity]> {
+        None
+    }
+
+    fn matches(_state: &Self::State<'_>, _entity: Entity) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'q>(state: &'q Self::State<'_>, entity: Entity) -> Self::Item<'q> {
+        if Q::matches(state, entity) {
+            Some(Q::fetch(state, entity))
+        } else {
+            None
+        }
+    }
+}
+
+unsafe impl<Q: ReadOnlyQueryData> ReadOnlyQueryData for Option<Q> {}
+
+pub struct With<T: Component>(PhantomData<T>);
+
+pub struct Without<T: Component>(PhantomData<T>);
+
+impl<T: Component> QueryFilter for With<T> {
+    type State<'w> = Option<FilterStorage<'w, T>>;
+
+    fn update_access(access: &mut Access) {
+        access.add_filter_read::<T>();
+    }
+
+    fn borrow_state<'w>(
+        world: &'w World,
+        _ticks: SystemTicks,
+        data_access: &Access,
+    ) -> Self::State<'w> {
+        borrow_filter::<T>(world, data_access)
+    }
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+        state
+            .as_ref()
+            .is_some_and(|storage| storage.contains(entity))
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    type State<'w> = Option<FilterStorage<'w, T>>;
+
+    fn update_access(access: &mut Access) {
+        access.add_filter_read::<T>();
+    }
+
+    fn borrow_state<'w>(
+        world: &'w World,
+        _ticks: SystemTicks,
+        data_access: &Access,
+    ) -> Self::State<'w> {
+        borrow_filter::<T>(world, data_access)
+    }
+
+    fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+        !state
+            .as_ref()
+            .is_some_and(|storage| storage.contains(entity))
+    }
+}
+
+pub struct Added<T: Component>(PhantomData<T>);
+
+pub struct Changed<T: Component>(PhantomData<T>);
+
+impl<T: Component> QueryFilter for Added<T> {
+    type State<'w> = (Option<FilterStorage<'w, T>>, SystemTicks);
+
+    fn update_access(access: &mut Access) {
+        access.add_filter_read::<T>();
+    }
+
+    fn borrow_state<'w>(
+        world: &'w World,
+        ticks: SystemTicks,
+        data_access: &Access,
+    ) -> Self::State<'w> {
+        (borrow_filter::<T>(world, data_access), ticks)
+    }
+
+    fn matches((state, ticks): &Self::State<'_>, entity: Entity) -> bool {
+        state
+            .as_ref()
+            .and_then(|storage| storage.get_ticks(entity))
+            .is_some_and(|component_ticks| component_ticks.is_added(*ticks))
+    }
+}
+
+impl<T: Component> QueryFilter for Changed<T> {
+    type State<'w> = (Option<FilterStorage<'w, T>>, SystemTicks);
+
+    fn update_access(access: &mut Access) {
+        access.add_filter_read::<T>();
+    }
+
+    fn borrow_state<'w>(
+        world: &'w World,
+        ticks: SystemTicks,
+        data_access: &Access,
+    ) -> Self::State<'w> {
+        (borrow_filter::<T>(world, data_access), ticks)
+    }
+
+    fn matches((state, ticks): &Self::State<'_>, entity: Entity) -> bool {
+        state
+            .as_ref()
+            .and_then(|storage| storage.get_ticks(entity))
+            .is_some_and(|component_ticks| component_ticks.is_changed(*ticks))
+    }
+}
+
+macro_rules! impl_query_for_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        impl<$($name: QueryData),*> QueryData for ($($name,)*) {
+            type State<'w> = ($($name::State<'w>,)*);
+            type Item<'q> = ($($name::Item<'q>,)*);
+
+            fn update_access(access: &mut Access) {
+                $($name::update_access(access);)*
+            }
+
+            fn borrow_state(world: &World, ticks: SystemTicks) -> Self::State<'_> {
+                ($($name::borrow_state(world, ticks),)*)
+            }
+
+            fn get_candidates<'s>(state: &'s Self::State<'_>) -> Option<&'s [Entity]> {
+                let ($($name,)*) = state;
+                let candidates: Option<&[Entity]> = None;
+                $(
+                    let candidates = match ($name::get_candidates($name), candidates) {
+                        (Some(next), Some(current)) if next.len() >= current.len() => Some(current),
+                        (Some(next), _) => Some(next),
+                        (None, current) => current,
+                    };
+                )*
+                candidates
+            }
+
+            fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+                let ($($name,)*) = state;
+                true $(&& $name::matches($name, entity))*
+            }
+
+            unsafe fn fetch<'q>(state: &'q Self::State<'_>, entity: Entity) -> Self::Item<'q> {
+                let ($($name,)*) = state;
+                ($($name::fetch($name, entity),)*)
+            }
+        }
+
+        unsafe impl<$($name: ReadOnlyQueryData),*> ReadOnlyQueryData for ($($name,)*) {}
+
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            type State<'w> = ($($name::State<'w>,)*);
+
+            fn update_access(access: &mut Access) {
+                $($name::update_access(access);)*
+            }
+
+            fn borrow_state<'w>(
+                world: &'w World,
+                ticks: SystemTicks,
+                data_access: &Access,
+            ) -> Self::State<'w> {
+                ($($name::borrow_state(world, ticks, data_access),)*)
+            }
+
+            fn matches(state: &Self::State<'_>, entity: Entity) -> bool {
+                let ($($name,)*) = state;
+                true $(&& $name::matches($name, entity))*
+            }
+        }
+    };
+}
+
+impl_query_for_tuple!();
+impl_query_for_tuple!(A);
+impl_query_for_tuple!(A, B);
+impl_query_for_tuple!(A, B, C);
+impl_query_for_tuple!(A, B, C, D);
+impl_query_for_tuple!(A, B, C, D, E);
+impl_query_for_tuple!(A, B, C, D, E, F);
+impl_query_for_tuple!(A, B, C, D, E, F, G);
+impl_query_for_tuple!(A, B, C, D, E, F, G, H);
+
+pub struct Query<'w, Q: QueryData, F: QueryFilter = ()> {
+    world: &'w World,
+    data: Q::State<'w>,
+    filter: F::State<'w>,
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> Query<'w, Q, F> {
+    pub fn new(world: &'w World) -> Self {
+        Self::new_with_ticks(world, world.get_ticks())
+    }
+
+    pub fn new_with_ticks(world: &'w World, ticks: SystemTicks) -> Self {
+        let mut data_access = Access::new();
+        Q::update_access(&mut data_access);
+
+        // the filter goes first so it can find the storage before the data locks it
+        let filter = F::borrow_state(world, ticks, &data_access);
+
+        Self {
+            world,
+            data: Q::borrow_state(world, ticks),
+            filter,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> QueryIter<'_, 'w, Q, F> {
+        QueryIter::new(self)
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<Q::Item<'_>> {
+        self.contains(entity)
+            .then(|| unsafe { Q::fetch(&self.data, entity) })
+    }
+
+    pub fn for_each_mut(&mut self, mut function: impl FnMut(Q::Item<'_>)) {
+        for item in self.iter_mut() {
+            function(item);
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.world.is_alive(entity)
+            && Q::matches(&self.data, entity)
+            && F::matches(&self.filter, entity)
+    }
+
+    pub fn count(&self) -> usize {
+        self.get_candidates()
+            .filter(|entity| Q::matches(&self.data, *entity) && F::matches(&self.filter, *entity))
+            .count()
+    }
+
+    fn get_candidates(&self) -> Candidates<'_> {
+        match Q::get_candidates(&self.data) {
+            Some(entities) => Candidates::Storage(entities.iter()),
+            None => Candidates::World(
+                self.world
+                    .get_entities()
+                    .iter()
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+        }
+    }
+}
+
+impl<'w, Q: ReadOnlyQueryData, F: QueryFilter> Query<'w, Q, F> {
+    pub fn iter(&self) -> QueryIter<'_, 'w, Q, F> {
+        QueryIter::new(self)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<Q::Item<'_>> {
+        self.contains(entity)
+            .then(|| unsafe { Q::fetch(&self.data, entity) })
+    }
+
+    pub fn for_each(&self, mut function: impl FnMut(Q::Item<'_>)) {
+        for item in self.iter() {
+            function(item);
+        }
+    }
+}
+
+enum Candidates<'q> {
+    Storage(slice::Iter<'q, Entity>),
+    World(vec::IntoIter<Entity>),
+}
+
+impl Iterator for Candidates<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        match self {
+            Candidates::Storage(entities) => entities.next().copied(),
+            Candidates::World(entities) => entities.next(),
+        }
+    }
+}
+
+pub struct QueryIter<'q, 'w, Q: QueryData, F: QueryFilter> {
+    query: &'q Query<'w, Q, F>,
+    candidates: Candidates<'q>,
+}
+
+impl<'q, 'w, Q: QueryData, F: QueryFilter> QueryIter<'q, 'w, Q, F> {
+    fn new(query: &'q Query<'w, Q, F>) -> Self {
+        Self {
+            query,
+            candidates: query.get_candidates(),
+        }
+    }
+}
+
+impl<'q, Q: QueryData, F: QueryFilter> Iterator for QueryIter<'q, '_, Q, F> {
+    type Item = Q::Item<'q>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let query = self.query;
+
+        self.candidates.by_ref().find_map(|entity| {
+            (Q::matches(&query.data, entity) && F::matches(&query.filter, entity))
+                .then(|| unsafe { Q::fetch(&query.data, entity) })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ecs::{
+        resource::{ResMut, Resource},
+        system::{Local, Schedule, Stage},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {}
+
+    struct Shield;
+
+    impl Component for Shield {}
+
+    fn cap_changed_health(mut query: Query<&mut Health, Changed<Health>>) {
+        for mut health in query.iter_mut() {
+            if health.0 > 100 {
+                health.0 = 100;
+            }
+        }
+    }
+
+    #[test]
+    fn filters_share_the_query_write() {
+        let mut world = World::new();
+        let healed = world.spawn(Health(50));
+        let other = world.spawn((Health(80), Shield));
+        world.clear_trackers();
+
+        world.get_mut::<Health>(healed).unwrap().0 = 150;
+
+        let mut query =
+            world.query_filtered::<(Entity, &mut Health), (Changed<Health>, With<Health>)>();
+        assert_eq!(query.count(), 1);
+        for (entity, mut health) in query.iter_mut() {
+            assert_eq!(entity, healed);
+            health.0 += 1;
+        }
+        drop(query);
+
+        let mut shielded = world.query_filtered::<&mut Health, With<Shield>>();
+        assert_eq!(shielded.get_mut(other).unwrap().0, 80);
+        drop(shielded);
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, cap_changed_health);
+        schedule.run(&mut world);
+
+        assert_eq!(*world.get::<Health>(healed).unwrap(), Health(100));
+        assert_eq!(*world.get::<Health>(other).unwrap(), Health(80));
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting access")]
+    fn filters_still_conflict_with_other_queries() {
+        fn split(_health: Query<&mut Health>, _shields: Query<&Shield, Changed<Health>>) {}
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, split);
+        schedule.run(&mut World::new());
+    }
+
+    #[test]
+    fn filters_and_optional_components() {
+        let mut world = World::new();
+        let plain = world.spawn(Health(1));
+        let shielded = world.spawn((Health(2), Shield));
+        let bare = world.spawn_empty();
+
+        let mut query = world.query_filtered::<(Entity, &mut Health), Without<Shield>>();
+        let mut matched = Vec::new();
+        for (entity, mut health) in query.iter_mut() {
+            health.0 += 10;
+            matched.push(entity);
+        }
+        assert_eq!(matched, vec![plain]);
+        assert!(query.get_mut(shielded).is_none());
+        drop(query);
+        assert_eq!(*world.get::<Health>(plain).unwrap(), Health(11));
+
+        let query = world.query::<(Entity, &Health, Option<&Shield>)>();
+        assert!(query.get(plain).unwrap().2.is_none());
+        assert!(query.get(shielded).unwrap().2.is_some());
+        assert!(query.get(bare).is_none());
+
+        let query = world.query_filtered::<Entity, With<Shield>>();
+        assert_eq!(query.iter().collect::<Vec<_>>(), vec![shielded]);
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn aliasing_borrows_panic() {
+        let mut world = World::new();
+        world.spawn(Health(1));
+        let _query = world.query::<(&Health, &mut Health)>();
+    }
+
+    #[derive(Default)]
+    struct Seen(Vec<(usize, usize)>);
+
+    impl Resource for Seen {}
+
+    fn heal_first(mut query: Query<(Entity, &mut Health)>, mut frame: Local<u32>) {
+        *frame += 1;
+        for (entity, mut health) in query.iter_mut() {
+            // reading through Mut must not mark the component as changed
+            let _ = health.0;
+            if entity.get_index() == 0 && *frame == 2 {
+                health.0 += 1;
+            }
+        }
+    }
+
+    fn observe(
+        changed: Query<Entity, Changed<Health>>,
+        added: Query<Entity, Added<Health>>,
+        mut seen: ResMut<Seen>,
+    ) {
+        seen.0.push((changed.count(), added.count()));
+    }
+
+    #[test]
+    fn change_ticks_track_additions_and_writes() {
+        let mut world = World::new();
+        world.init_resource::<Seen>();
+        world.spawn(Health(1));
+        world.spawn(Health(2));
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, heal_first);
+        schedule.add_system(Stage::PostUpdate, observe);
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+
+        let spawned = world.spawn(Health(3));
+        schedule.run(&mut world);
+        world.get_mut::<Health>(spawned).unwrap().0 = 5;
+        schedule.run(&mut world);
+
+        assert_eq!(
+            world.resource::<Seen>().0,
+            vec![(2, 2), (1, 0), (0, 0), (1, 1), (1, 0)]
+        );
+
+        world.clear_trackers();
+        assert_eq!(world.query_filtered::<Entity, Changed<Health>>().count(), 0);
+    }
+}
@@ -0,0 +1,624 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+};
+
+use cgmath::{Matrix4, Vector2, Vector3, Vector4};
+
+use super::{component::Component, entity::Entity, resource::Resource, world::World};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReflectValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl ReflectValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ReflectValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            ReflectValue::Float(value) => Some(*value),
+            ReflectValue::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            ReflectValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ReflectValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub type_name: &'static str,
+}
+
+pub trait Reflect: Any + Send + Sync {
+    fn get_type_name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
+    fn get_field_infos() -> Vec<FieldInfo>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    fn get_field_names(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn get_field(&self, _name: &str) -> Option<&dyn Reflect> {
+        None
+    }
+
+    fn get_field_mut(&mut self, _name: &str) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    // overridden by types that expose computed fields with no storage to borrow
+    fn with_field(&self, name: &str, function: &mut dyn FnMut(&dyn Reflect)) -> bool {
+        match self.get_field(name) {
+            Some(field) => {
+                function(field);
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    fn with_field_mut(&mut self, name: &str, function: &mut dyn FnMut(&mut dyn Reflect)) -> bool {
+        match self.get_field_mut(name) {
+            Some(field) => {
+                function(field);
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    fn get_value(&self) -> Option<ReflectValue> {
+        None
+    }
+
+    fn set_value(&mut self, _value: &ReflectValue) -> bool {
+        false
+    }
+
+    fn accepts_value(&self, _value: &ReflectValue) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl dyn Reflect {
+    pub fn with_path(&self, path: &str, function: &mut dyn FnMut(&dyn Reflect)) -> bool {
+        match split_path(path.trim_start_matches('.')) {
+            ("", _) => {
+                function(self);
+                true
+            }
+
+            (name, field_path) => {
+                let mut found = false;
+                self.with_field(name, &mut |field| {
+                    found = field.with_path(field_path, function)
+                });
+                found
+            }
+        }
+    }
+
+    pub fn with_path_mut(
+        &mut self,
+        path: &str,
+        function: &mut dyn FnMut(&mut dyn Reflect),
+    ) -> bool {
+        match split_path(path.trim_start_matches('.')) {
+            ("", _) => {
+                function(self);
+                true
+            }
+
+            (name, field_path) => {
+                let mut found = false;
+                self.with_field_mut(name, &mut |field| {
+                    found = field.with_path_mut(field_path, function)
+                });
+                found
+            }
+        }
+    }
+
+    pub fn get_path_value(&self, path: &str) -> Option<ReflectValue> {
+        let mut value = None;
+        self.with_path(path, &mut |field| value = field.get_value());
+        value
+    }
+
+    pub fn downcast_ref<T: Reflect>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Reflect>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+#[macro_export]
+macro_rules! impl_reflect_struct {
+    ($struct_name:ty { $($field:tt: $field_type:ty),* $(,)? }) => {
+        impl $crate::engine::ecs::reflect::Reflect for $struct_name {
+            fn get_field_infos() -> Vec<$crate::engine::ecs::reflect::FieldInfo> {
+                vec![$($crate::engine::ecs::reflect::FieldInfo {
+                    name: stringify!($field),
+                    type_name: std::any::type_name::<$field_type>(),
+                }),*]
+            }
+
+            fn get_field_names(&self) -> Vec<&'static str> {
+                vec![$(stringify!($field)),*]
+            }
+
+            fn get_field(&self, name: &str) -> Option<&dyn $crate::engine::ecs::reflect::Reflect> {
+                match name {
+                    $(stringify!($field) => Some(&self.$field),)*
+                    _ => None,
+                }
+            }
+
+            fn get_field_mut(
+                &mut self,
+                name: &str,
+            ) -> Option<&mut dyn $crate::engine::ecs::reflect::Reflect> {
+                match name {
+                    $(stringify!($field) => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+
+            fn as_any(&self) -> &dyn std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+                self
+            }
+        }
+    };
+}
+
+macro_rules! impl_reflect_value {
+    ($value_type:ty, |$read:ident| $get:expr, |$write:ident| $set:expr) => {
+        impl Reflect for $value_type {
+            fn get_value(&self) -> Option<ReflectValue> {
+                let $read = self;
+                Some($get)
+            }
+
+            fn set_value(&mut self, $write: &ReflectValue) -> bool {
+                match $set {
+                    Some(value) => {
+                        *self = value;
+                        true
+                    }
+
+                    None => false,
+                }
+            }
+
+            fn accepts_value(&self, $write: &ReflectValue) -> bool {
+                $set.is_some()
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+    };
+}
+
+impl_reflect_value!(
+    bool,
+    |value| ReflectValue::Bool(*value),
+    |value| value.as_bool()
+);
+impl_reflect_value!(
+    f32,
+    |value| ReflectValue::Float(*value as f64),
+    |value| value.as_float().map(|value| value as f32)
+);
+impl_reflect_value!(
+    f64,
+    |value| ReflectValue::Float(*value),
+    |value| value.as_float()
+);
+impl_reflect_value!(
+    i32,
+    |value| ReflectValue::Int(*value as i64),
+    |value| value.as_int().and_then(|value| i32::try_from(value).ok())
+);
+impl_reflect_value!(
+    u32,
+    |value| ReflectValue::Int(*value as i64),
+    |value| value.as_int().and_then(|value| u32::try_from(value).ok())
+);
+impl_reflect_value!(
+    i64,
+    |value| ReflectValue::Int(*value),
+    |value| value.as_int()
+);
+impl_reflect_value!(
+    usize,
+    |value| ReflectValue::Int(*value as i64),
+    |value| value.as_int().and_then(|value| usize::try_from(value).ok())
+);
+impl_reflect_value!(
+    String,
+    |value| ReflectValue::String(value.clone()),
+    |value| value.as_str().map(str::to_string)
+);
+
+impl_reflect_struct!(Vector2<f32> { x: f32, y: f32 });
+impl_reflect_struct!(Vector3<f32> { x: f32, y: f32, z: f32 });
+impl_reflect_struct!(Vector4<f32> { x: f32, y: f32, z: f32, w: f32 });
+impl_reflect_struct!(Matrix4<f32> {
+    x: Vector4<f32>,
+    y: Vector4<f32>,
+    z: Vector4<f32>,
+    w: Vector4<f32>,
+});
+
+type ReadComponentFn = fn(&World, Entity, &mut dyn FnMut(&dyn Reflect)) -> bool;
+type WriteComponentFn = fn(&mut World, Entity, &mut dyn FnMut(&mut dyn Reflect)) -> bool;
+type ReadResourceFn = fn(&World, &mut dyn FnMut(&dyn Reflect)) -> bool;
+type WriteResourceFn = fn(&World, &mut dyn FnMut(&mut dyn Reflect)) -> bool;
+
+#[derive(Clone)]
+pub struct ComponentRegistration {
+    name: &'static str,
+    type_id: TypeId,
+    type_name: &'static str,
+    fields: Vec<FieldInfo>,
+    with_component: ReadComponentFn,
+    with_component_mut: WriteComponentFn,
+    insert_default: fn(&mut World, Entity),
+    create_default: fn() -> Box<dyn Reflect>,
+}
+
+impl ComponentRegistration {
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn get_type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn get_type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn get_fields(&self) -> &[FieldInfo] {
+        &self.fields
+    }
+
+    pub fn has(&self, world: &World, entity: Entity) -> bool {
+        (self.with_component)(world, entity, &mut |_| ())
+    }
+
+    pub fn with_component(
+        &self,
+        world: &World,
+        entity: Entity,
+        function: &mut dyn FnMut(&dyn Reflect),
+    ) -> bool {
+        (self.with_component)(world, entity, function)
+    }
+
+    pub fn with_component_mut(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        function: &mut dyn FnMut(&mut dyn Reflect),
+    ) -> bool {
+        (self.with_component_mut)(world, entity, function)
+    }
+
+    pub fn insert_default(&self, world: &mut World, entity: Entity) {
+        (self.insert_default)(world, entity)
+    }
+
+    pub fn create_default(&self) -> Box<dyn Reflect> {
+        (self.create_default)()
+    }
+}
+
+#[derive(Clone)]
+pub struct ResourceRegistration {
+    name: &'static str,
+    type_id: TypeId,
+    type_name: &'static str,
+    fields: Vec<FieldInfo>,
+    with_resource: ReadResourceFn,
+    with_resource_mut: WriteResourceFn,
+}
+
+impl ResourceRegistration {
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn get_type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    pub fn get_type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn get_fields(&self) -> &[FieldInfo] {
+        &self.fields
+    }
+
+    pub fn with_resource(&self, world: &World, function: &mut dyn FnMut(&dyn Reflect)) -> bool {
+        (self.with_resource)(world, function)
+    }
+
+    pub fn with_resource_mut(
+        &self,
+        world: &World,
+        function: &mut dyn FnMut(&mut dyn Reflect),
+    ) -> bool {
+        (self.with_resource_mut)(world, function)
+    }
+}
+
+#[derive(Default)]
+pub struct TypeRegistry {
+    components: Vec<ComponentRegistration>,
+    resources: Vec<ResourceRegistration>,
+    names: HashMap<&'static str, usize>,
+    resource_names: HashMap<&'static str, usize>,
+}
+
+impl Resource for TypeRegistry {}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_component<T: Component + Reflect + Default>(&mut self, name: &'static str) {
+        if self.names.contains_key(name) {
+            panic!("Component name {name} is already registered.");
+        }
+
+        self.names.insert(name, self.components.len());
+        self.components.push(ComponentRegistration {
+            name,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            fields: T::get_field_infos(),
+            with_component: |world, entity, function| match world.get::<T>(entity) {
+                Some(component) => {
+                    function(&*component);
+                    true
+                }
+
+                None => false,
+            },
+            with_component_mut: |world, entity, function| match world.get_mut::<T>(entity) {
+                Some(mut component) => {
+                    function(&mut *component);
+                    true
+                }
+
+                None => false,
+            },
+            insert_default: |world, entity| {
+                world.insert(entity, T::default());
+            },
+            create_default: || Box::new(T::default()),
+        });
+    }
+
+    pub fn register_resource<T: Resource + Reflect>(&mut self, name: &'static str) {
+        if self.resource_names.contains_key(name) {
+            panic!("Resource name {name} is already registered.");
+        }
+
+        self.resource_names.insert(name, self.resources.len());
+        self.resources.push(ResourceRegistration {
+            name,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            fields: T::get_field_infos(),
+            with_resource: |world, function| match world.get_resource::<T>() {
+                Some(resource) => {
+                    function(&*resource);
+                    true
+                }
+
+                None => false,
+            },
+            with_resource_mut: |world, function| match world.get_resource_mut::<T>() {
+                Some(mut resource) => {
+                    function(&mut *resource);
+                    true
+                }
+
+                None => false,
+            },
+        });
+    }
+
+    pub fn get_component(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.names.get(name).map(|index| &self.components[*index])
+    }
+
+    pub fn get_component_by_type(&self, type_id: TypeId) -> Option<&ComponentRegistration> {
+        self.components
+            .iter()
+            .find(|registration| registration.type_id == type_id)
+    }
+
+    pub fn get_resource(&self, name: &str) -> Option<&ResourceRegistration> {
+        self.resource_names.get(name).map(|index| &self.resources[*index])
+    }
+
+    pub fn get_components(&self) -> &[ComponentRegistration] {
+        &self.components
+    }
+
+    pub fn get_resources(&self) -> &[ResourceRegistration] {
+        &self.resources
+    }
+}
+
+fn split_path(path: &str) -> (&str, &str) {
+    path.split_once('.').unwrap_or((path, ""))
+}
+
+pub fn get_component_path(world: &World, entity: Entity, path: &str) -> Option<ReflectValue> {
+    let (name, field_path) = split_path(path);
+    let registration = world
+        .get_resource::<TypeRegistry>()?
+        .get_component(name)?
+        .clone();
+
+    let mut value = None;
+    registration.with_component(world, entity, &mut |component| {
+        value = component.get_path_value(field_path);
+    });
+    value
+}
+
+pub fn set_component_path(
+    world: &mut World,
+    entity: Entity,
+    path: &str,
+    value: &ReflectValue,
+) -> bool {
+    let (name, field_path) = split_path(path);
+    let registration = match world
+        .get_resource::<TypeRegistry>()
+        .and_then(|registry| registry.get_component(name).cloned())
+    {
+        Some(registration) => registration,
+        None => return false,
+    };
+
+    let mut accepted = false;
+    registration.with_component(world, entity, &mut |component| {
+        component.with_path(field_path, &mut |field| {
+            accepted = field.accepts_value(value)
+        });
+    });
+
+    if !accepted {
+        return false;
+    }
+
+    let mut updated = false;
+    registration.with_component_mut(world, entity, &mut |component| {
+        component.with_path_mut(field_path, &mut |field| updated = field.set_value(value));
+    });
+    updated
+}
+
+pub fn get_resource_path(world: &World, path: &str) -> Option<ReflectValue> {
+    let (name, field_path) = split_path(path);
+    let registration = world
+        .get_resource::<TypeRegistry>()?
+        .get_resource(name)?
+        .clone();
+
+    let mut value = None;
+    registration.with_resource(world, &mut |resource| {
+        value = resource.get_path_value(field_path);
+    });
+    value
+}
+
+pub fn set_resource_path(world: &World, path: &str, value: &ReflectValue) -> bool {
+    let (name, field_path) = split_path(path);
+    let registration = match world
+        .get_resource::<TypeRegistry>()
+        .and_then(|registry| registry.get_resource(name).cloned())
+    {
+        Some(registration) => registration,
+        None => return false,
+    };
+
+    let mut accepted = false;
+    registration.with_resource(world, &mut |resource| {
+        resource.with_path(field_path, &mut |field| {
+            accepted = field.accepts_value(value)
+        });
+    });
+
+    if !accepted {
+        return false;
+    }
+
+    let mut updated = false;
+    registration.with_resource_mut(world, &mut |resource| {
+        resource.with_path_mut(field_path, &mut |field| updated = field.set_value(value));
+    });
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_without_a_registry_are_missing() {
+        let mut world = World::new();
+        let entity = world.spawn_empty();
+        let value = ReflectValue::Float(1.0);
+
+        assert_eq!(
+            get_component_path(&world, entity, "transform.scale.x"),
+            None
+        );
+        assert!(!set_component_path(
+            &mut world,
+            entity,
+            "transform.scale.x",
+            &value
+        ));
+        assert_eq!(get_resource_path(&world, "camera.fov"), None);
+        assert!(!set_resource_path(&world, "camera.fov", &value));
+    }
+}
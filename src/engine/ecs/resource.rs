@@ -0,0 +1,48 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+pub trait Resource: Send + Sync + 'static {}
+
+pub struct Res<'w, T: Resource> {
+    guard: RwLockReadGuard<'w, T>,
+}
+
+impl<'w, T: Resource> Res<'w, T> {
+    pub(crate) fn new(guard: RwLockReadGuard<'w, T>) -> Self {
+        Self { guard }
+    }
+}
+
+impl<T: Resource> Deref for Res<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+pub struct ResMut<'w, T: Resource> {
+    guard: RwLockWriteGuard<'w, T>,
+}
+
+impl<'w, T: Resource> ResMut<'w, T> {
+    pub(crate) fn new(guard: RwLockWriteGuard<'w, T>) -> Self {
+        Self { guard }
+    }
+}
+
+impl<T: Resource> Deref for ResMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: Resource> DerefMut for ResMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
@@ -0,0 +1,962 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use super::{
+    access::Access,
+    command::{CommandQueue, Commands},
+    component::{SystemTicks, Tick},
+    event::{Event, EventReader, EventWriter, Events},
+    query::{Query, QueryData, QueryFilter},
+    resource::{Res, ResMut, Resource},
+    world::World,
+};
+
+pub trait SystemParam {
+    type State: Send + Sync + 'static;
+    type Item<'w, 's>;
+
+    fn update_access(access: &mut Access);
+
+    fn init_state(world: &mut World) -> Self::State;
+
+    fn get_param<'w, 's>(
+        state: &'s mut Self::State,
+        world: &'w World,
+        ticks: SystemTicks,
+    ) -> Self::Item<'w, 's>;
+
+    fn apply(_state: &mut Self::State, _world: &mut World) {}
+}
+
+pub type SystemParamItem<'w, 's, P> = <P as SystemParam>::Item<'w, 's>;
+
+impl<Q: QueryData + 'static, F: QueryFilter + 'static> SystemParam for Query<'_, Q, F> {
+    type State = ();
+    type Item<'w, 's> = Query<'w, Q, F>;
+
+    // collected per query first so filters can lean on the same query's writes
+    fn update_access(access: &mut Access) {
+        let mut query_access = Access::new();
+        Q::update_access(&mut query_access);
+        F::update_access(&mut query_access);
+        access.extend(&query_access);
+    }
+
+    fn init_state(_world: &mut World) -> Self::State {}
+
+    fn get_param<'w, 's>(
+        _state: &'s mut Self::State,
+        world: &'w World,
+        ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        Query::new_with_ticks(world, ticks)
+    }
+}
+
+impl<T: Resource> SystemParam for Res<'_, T> {
+    type State = ();
+    type Item<'w, 's> = Res<'w, T>;
+
+    fn update_access(access: &mut Access) {
+        access.add_resource_read::<T>();
+    }
+
+    fn init_state(_world: &mut World) -> Self::State {}
+
+    fn get_param<'w, 's>(
+        _state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        world.resource::<T>()
+    }
+}
+
+impl<T: Resource> SystemParam for ResMut<'_, T> {
+    type State = ();
+    type Item<'w, 's> = ResMut<'w, T>;
+
+    fn update_access(access: &mut Access) {
+        access.add_resource_write::<T>();
+    }
+
+    fn init_state(_world: &mut World) -> Self::State {}
+
+    fn get_param<'w, 's>(
+        _state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        world.resource_mut::<T>()
+    }
+}
+
+impl<T: Resource> SystemParam for Option<Res<'_, T>> {
+    type State = ();
+    type Item<'w, 's> = Option<Res<'w, T>>;
+
+    fn update_access(access: &mut Access) {
+        access.add_resource_read::<T>();
+    }
+
+    fn init_state(_world: &mut World) -> Self::State {}
+
+    fn get_param<'w, 's>(
+        _state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        world.get_resource::<T>()
+    }
+}
+
+impl<T: Resource> SystemParam for Option<ResMut<'_, T>> {
+    type State = ();
+    type Item<'w, 's> = Option<ResMut<'w, T>>;
+
+    fn update_access(access: &mut Access) {
+        access.add_resource_write::<T>();
+    }
+
+    fn init_state(_world: &mut World) -> Self::State {}
+
+    fn get_param<'w, 's>(
+        _state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        world.get_resource_mut::<T>()
+    }
+}
+
+impl<T: Event> SystemParam for EventWriter<'_, T> {
+    type State = ();
+    type Item<'w, 's> = EventWriter<'w, T>;
+
+    fn update_access(access: &mut Access) {
+        access.add_resource_write::<Events<T>>();
+    }
+
+    fn init_state(world: &mut World) -> Self::State {
+        world.add_event::<T>();
+    }
+
+    fn get_param<'w, 's>(
+        _state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        EventWriter::new(world.resource_mut::<Events<T>>())
+    }
+}
+
+impl<T: Event> SystemParam for EventReader<'_, '_, T> {
+    type State = usize;
+    type Item<'w, 's> = EventReader<'w, 's, T>;
+
+    fn update_access(access: &mut Access) {
+        access.add_resource_read::<Events<T>>();
+    }
+
+    fn init_state(world: &mut World) -> Self::State {
+        world.add_event::<T>();
+        0
+    }
+
+    fn get_param<'w, 's>(
+        state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        EventReader::new(world.resource::<Events<T>>(), state)
+    }
+}
+
+impl SystemParam for Commands<'_, '_> {
+    type State = CommandQueue;
+    type Item<'w, 's> = Commands<'w, 's>;
+
+    fn update_access(_access: &mut Access) {}
+
+    fn init_state(_world: &mut World) -> Self::State {
+        CommandQueue::new()
+    }
+
+    fn get_param<'w, 's>(
+        state: &'s mut Self::State,
+        world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        Commands::new(state, world)
+    }
+
+    fn apply(state: &mut Self::State, world: &mut World) {
+        state.apply(world);
+    }
+}
+
+pub struct Local<'s, T: Default + Send + Sync + 'static>(&'s mut T);
+
+impl<T: Default + Send + Sync + 'static> SystemParam for Local<'_, T> {
+    type State = T;
+    type Item<'w, 's> = Local<'s, T>;
+
+    fn update_access(_access: &mut Access) {}
+
+    fn init_state(_world: &mut World) -> Self::State {
+        T::default()
+    }
+
+    fn get_param<'w, 's>(
+        state: &'s mut Self::State,
+        _world: &'w World,
+        _ticks: SystemTicks,
+    ) -> Self::Item<'w, 's> {
+        Local(state)
+    }
+}
+
+impl<T: Default + Send + Sync + 'static> Deref for Local<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T: Default + Send + Sync + 'static> DerefMut for Local<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0
+    }
+}
+
+macro_rules! impl_system_param_for_tuple {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        impl<$($param: SystemParam),*> SystemParam for ($($param,)*) {
+            type State = ($($param::State,)*);
+            type Item<'w, 's> = ($($param::Item<'w, 's>,)*);
+
+            fn update_access(access: &mut Access) {
+                $($param::update_access(access);)*
+            }
+
+            fn init_state(world: &mut World) -> Self::State {
+                ($($param::init_state(world),)*)
+            }
+
+            fn get_param<'w, 's>(
+                state: &'s mut Self::State,
+                world: &'w World,
+                ticks: SystemTicks,
+            ) -> Self::Item<'w, 's> {
+                let ($($param,)*) = state;
+                ($($param::get_param($param, world, ticks),)*)
+            }
+
+            fn apply(state: &mut Self::State, world: &mut World) {
+                let ($($param,)*) = state;
+                $($param::apply($param, world);)*
+            }
+        }
+    };
+}
+
+impl_system_param_for_tuple!();
+impl_system_param_for_tuple!(A);
+impl_system_param_for_tuple!(A, B);
+impl_system_param_for_tuple!(A, B, C);
+impl_system_param_for_tuple!(A, B, C, D);
+impl_system_param_for_tuple!(A, B, C, D, E);
+impl_system_param_for_tuple!(A, B, C, D, E, F);
+impl_system_param_for_tuple!(A, B, C, D, E, F, G);
+impl_system_param_for_tuple!(A, B, C, D, E, F, G, H);
+
+pub trait SystemParamFunction<Marker>: Send + Sync + 'static {
+    type Param: SystemParam;
+
+    fn run(&mut self, param: SystemParamItem<Self::Param>);
+}
+
+macro_rules! impl_system_param_function {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<Function, $($param: SystemParam),*> SystemParamFunction<fn($($param,)*)> for Function
+        where
+            Function: Send + Sync + 'static,
+            for<'a> &'a mut Function: FnMut($($param),*) + FnMut($(SystemParamItem<$param>),*),
+        {
+            type Param = ($($param,)*);
+
+            fn run(&mut self, param: SystemParamItem<Self::Param>) {
+                #[allow(clippy::too_many_arguments)]
+                fn call_inner<$($param,)*>(mut function: impl FnMut($($param,)*), $($param: $param,)*) {
+                    function($($param,)*)
+                }
+
+                let ($($param,)*) = param;
+                call_inner(self, $($param),*)
+            }
+        }
+    };
+}
+
+impl_system_param_function!();
+impl_system_param_function!(A);
+impl_system_param_function!(A, B);
+impl_system_param_function!(A, B, C);
+impl_system_param_function!(A, B, C, D);
+impl_system_param_function!(A, B, C, D, E);
+impl_system_param_function!(A, B, C, D, E, F);
+impl_system_param_function!(A, B, C, D, E, F, G);
+impl_system_param_function!(A, B, C, D, E, F, G, H);
+
+pub trait System: Send + Sync {
+    fn get_name(&self) -> &str;
+
+    fn get_access(&self) -> &Access;
+
+    fn initialize(&mut self, world: &mut World);
+
+    fn run(&mut self, world: &World);
+
+    fn apply_deferred(&mut self, world: &mut World);
+}
+
+pub struct FunctionSystem<Marker, F: SystemParamFunction<Marker>> {
+    function: F,
+    state: Option<<F::Param as SystemParam>::State>,
+    access: Access,
+    last_run: Tick,
+    name: String,
+    marker: PhantomData<fn() -> Marker>,
+}
+
+impl<Marker: 'static, F: SystemParamFunction<Marker>> System for FunctionSystem<Marker, F> {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_access(&self) -> &Access {
+        &self.access
+    }
+
+    fn initialize(&mut self, world: &mut World) {
+        if self.state.is_some() {
+            return;
+        }
+
+        F::Param::update_access(&mut self.access);
+
+        let invalid = self.access.get_invalid();
+        if !invalid.is_empty() {
+            panic!(
+                "System {} has conflicting access to {}.",
+                self.name,
+                invalid.join(", ")
+            );
+        }
+
+        self.state = Some(F::Param::init_state(world));
+    }
+
+    fn run(&mut self, world: &World) {
+        let state = self
+            .state
+            .as_mut()
+            .unwrap_or_else(|| panic!("System {} was not initialized.", self.name));
+
+        let ticks = SystemTicks {
+            last_run: self.last_run,
+            this_run: world.increment_change_tick(),
+        };
+
+        self.function.run(F::Param::get_param(state, world, ticks));
+        self.last_run = ticks.this_run;
+    }
+
+    fn apply_deferred(&mut self, world: &mut World) {
+        if let Some(state) = self.state.as_mut() {
+            F::Param::apply(state, world);
+        }
+    }
+}
+
+pub trait IntoSystem<Marker> {
+    type System: System + 'static;
+
+    fn into_system(self) -> Self::System;
+}
+
+impl<Marker: 'static, F: SystemParamFunction<Marker>> IntoSystem<Marker> for F {
+    type System = FunctionSystem<Marker, F>;
+
+    fn into_system(self) -> Self::System {
+        FunctionSystem {
+            function: self,
+            state: None,
+            access: Access::new(),
+            last_run: Tick::new(0),
+            name: std::any::type_name::<F>().to_string(),
+            marker: PhantomData,
+        }
+    }
+}
+
+pub struct SystemDescriptor {
+    system: Box<dyn System>,
+    label: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+impl SystemDescriptor {
+    pub fn new(system: Box<dyn System>) -> Self {
+        let label = get_short_name(system.get_name()).to_string();
+
+        Self {
+            system,
+            label,
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+}
+
+pub trait IntoSystemDescriptor<Marker>: Sized {
+    fn into_descriptor(self) -> SystemDescriptor;
+
+    fn label(self, label: &str) -> SystemDescriptor {
+        let mut descriptor = self.into_descriptor();
+        descriptor.label = label.to_string();
+        descriptor
+    }
+
+    fn before(self, label: &str) -> SystemDescriptor {
+        let mut descriptor = self.into_descriptor();
+        descriptor.before.push(label.to_string());
+        descriptor
+    }
+
+    fn after(self, label: &str) -> SystemDescriptor {
+        let mut descriptor = self.into_descriptor();
+        descriptor.after.push(label.to_string());
+        descriptor
+    }
+}
+
+impl IntoSystemDescriptor<()> for SystemDescriptor {
+    fn into_descriptor(self) -> SystemDescriptor {
+        self
+    }
+}
+
+impl<Marker, S: IntoSystem<Marker>> IntoSystemDescriptor<Marker> for S {
+    fn into_descriptor(self) -> SystemDescriptor {
+        SystemDescriptor::new(Box::new(self.into_system()))
+    }
+}
+
+fn get_short_name(name: &str) -> &str {
+    name.trim_end_matches("::{{closure}}")
+        .rsplit("::")
+        .next()
+        .unwrap_or(name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    PreUpdate,
+    FixedUpdate,
+    Update,
+    PostUpdate,
+    Render,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemAmbiguity {
+    pub first: String,
+    pub second: String,
+    pub conflicts: Vec<&'static str>,
+}
+
+#[derive(Default)]
+pub struct SystemStage {
+    systems: Vec<SystemDescriptor>,
+    batches: Option<Vec<Vec<usize>>>,
+    order: Vec<usize>,
+    ambiguities: Vec<SystemAmbiguity>,
+}
+
+impl SystemStage {
+    pub fn add_system(&mut self, descriptor: SystemDescriptor) {
+        self.systems.push(descriptor);
+        self.batches = None;
+    }
+
+    pub fn get_system_labels(&self) -> Vec<&str> {
+        self.systems
+            .iter()
+            .map(|descriptor| descriptor.label.as_str())
+            .collect()
+    }
+
+    pub fn get_ambiguities(&self) -> &[SystemAmbiguity] {
+        &self.ambiguities
+    }
+
+    pub fn initialize(&mut self, world: &mut World, stage: Stage) {
+        for descriptor in &mut self.systems {
+            descriptor.system.initialize(world);
+        }
+
+        if self.batches.is_some() {
+            return;
+        }
+
+        let dependencies = self.build_dependencies(stage);
+        let order = self.build_order(&dependencies, stage);
+
+        // reported through get_ambiguities, most are harmless and would flood the log
+        self.ambiguities = self.find_ambiguities(&dependencies, &order);
+
+        self.batches = Some(self.build_batches(&dependencies, &order));
+        self.order = order;
+    }
+
+    pub fn run(&mut self, world: &mut World, stage: Stage) {
+        self.initialize(world, stage);
+
+        let shared_world: &World = world;
+
+        for batch in self.batches.as_ref().unwrap() {
+            if let [index] = batch[..] {
+                self.systems[index].system.run(shared_world);
+                continue;
+            }
+
+            let systems = self
+                .systems
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| batch.contains(index))
+                .map(|(_, descriptor)| &mut descriptor.system);
+
+            rayon::scope(|scope| {
+                for system in systems {
+                    scope.spawn(move |_| system.run(shared_world));
+                }
+            });
+        }
+
+        for index in &self.order {
+            self.systems[*index].system.apply_deferred(world);
+        }
+    }
+
+    fn build_dependencies(&self, stage: Stage) -> Vec<BTreeSet<usize>> {
+        let mut dependencies = vec![BTreeSet::new(); self.systems.len()];
+
+        let find_labelled = |owner: &SystemDescriptor, label: &String| {
+            let matching = self
+                .systems
+                .iter()
+                .enumerate()
+                .filter(|(_, descriptor)| &descriptor.label == label)
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+
+            if matching.is_empty() {
+                panic!(
+                    "System {} in stage {stage:?} is ordered against unknown label {label}.",
+                    owner.label
+                );
+            }
+
+            matching
+        };
+
+        for (index, descriptor) in self.systems.iter().enumerate() {
+            for label in &descriptor.after {
+                dependencies[index].extend(find_labelled(descriptor, label));
+            }
+
+            for label in &descriptor.before {
+                for other in find_labelled(descriptor, label) {
+                    dependencies[other].insert(index);
+                }
+            }
+        }
+
+        dependencies
+    }
+
+    fn build_order(&self, dependencies: &[BTreeSet<usize>], stage: Stage) -> Vec<usize> {
+        let mut remaining = dependencies.iter().map(BTreeSet::len).collect::<Vec<_>>();
+        let mut ready = (0..self.systems.len())
+            .filter(|index| remaining[*index] == 0)
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::with_capacity(self.systems.len());
+
+        while let Some(index) = ready.pop_first() {
+            order.push(index);
+
+            for (dependent, requirements) in dependencies.iter().enumerate() {
+                if requirements.contains(&index) {
+                    remaining[dependent] -= 1;
+                    if remaining[dependent] == 0 {
+                        ready.insert(dependent);
+                    }
+                }
+            }
+        }
+
+        if order.len() != self.systems.len() {
+            let cycle = self
+                .find_cycle(dependencies)
+                .iter()
+                .map(|index| self.systems[*index].label.as_str())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            panic!("System ordering cycle detected in stage {stage:?}: {cycle}.");
+        }
+
+        order
+    }
+
+    fn build_batches(&self, dependencies: &[BTreeSet<usize>], order: &[usize]) -> Vec<Vec<usize>> {
+        let mut finished = vec![false; self.systems.len()];
+        let mut pending = order.to_vec();
+        let mut batches = Vec::new();
+
+        while !pending.is_empty() {
+            let mut batch: Vec<usize> = Vec::new();
+
+            for index in &pending {
+                let ready = dependencies[*index]
+                    .iter()
+                    .all(|dependency| finished[*dependency]);
+                let compatible = batch.iter().all(|other| {
+                    self.systems[*index]
+                        .system
+                        .get_access()
+                        .is_compatible(self.systems[*other].system.get_access())
+                });
+
+                if ready && compatible {
+                    batch.push(*index);
+                }
+            }
+
+            for index in &batch {
+                finished[*index] = true;
+            }
+
+            pending.retain(|index| !finished[*index]);
+            batches.push(batch);
+        }
+
+        batches
+    }
+
+    fn find_ambiguities(
+        &self,
+        dependencies: &[BTreeSet<usize>],
+        order: &[usize],
+    ) -> Vec<SystemAmbiguity> {
+        let mut ancestors = vec![BTreeSet::new(); self.systems.len()];
+
+        for index in order {
+            let mut reachable = BTreeSet::new();
+            for dependency in &dependencies[*index] {
+                reachable.insert(*dependency);
+                reachable.extend(ancestors[*dependency].iter().copied());
+            }
+            ancestors[*index] = reachable;
+        }
+
+        let mut ambiguities = Vec::new();
+
+        for (position, first) in order.iter().enumerate() {
+            for second in &order[position + 1..] {
+                if ancestors[*second].contains(first) || ancestors[*first].contains(second) {
+                    continue;
+                }
+
+                let conflicts = self.systems[*first]
+                    .system
+                    .get_access()
+                    .get_conflicts(self.systems[*second].system.get_access());
+
+                if !conflicts.is_empty() {
+                    ambiguities.push(SystemAmbiguity {
+                        first: self.systems[*first].label.clone(),
+                        second: self.systems[*second].label.clone(),
+                        conflicts,
+                    });
+                }
+            }
+        }
+
+        ambiguities
+    }
+
+    fn find_cycle(&self, dependencies: &[BTreeSet<usize>]) -> Vec<usize> {
+        fn visit(
+            index: usize,
+            dependencies: &[BTreeSet<usize>],
+            visited: &mut [bool],
+            path: &mut Vec<usize>,
+        ) -> Option<Vec<usize>> {
+            if let Some(start) = path.iter().position(|visiting| *visiting == index) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(index);
+                return Some(cycle);
+            }
+
+            if visited[index] {
+                return None;
+            }
+
+            visited[index] = true;
+            path.push(index);
+
+            for dependency in &dependencies[index] {
+                if let Some(cycle) = visit(*dependency, dependencies, visited, path) {
+                    return Some(cycle);
+                }
+            }
+
+            path.pop();
+            None
+        }
+
+        let mut visited = vec![false; dependencies.len()];
+
+        (0..dependencies.len())
+            .find_map(|index| visit(index, dependencies, &mut visited, &mut Vec::new()))
+            .map(|mut cycle| {
+                cycle.reverse();
+                cycle
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Default)]
+pub struct Schedule {
+    stages: BTreeMap<Stage, SystemStage>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_system<Marker>(
+        &mut self,
+        stage: Stage,
+        system: impl IntoSystemDescriptor<Marker>,
+    ) -> &mut Self {
+        self.stages
+            .entry(stage)
+            .or_default()
+            .add_system(system.into_descriptor());
+        self
+    }
+
+    pub fn get_stage(&self, stage: Stage) -> Option<&SystemStage> {
+        self.stages.get(&stage)
+    }
+
+    pub fn initialize(&mut self, world: &mut World) {
+        for (stage, systems) in &mut self.stages {
+            systems.initialize(world, *stage);
+        }
+    }
+
+    pub fn run(&mut self, world: &mut World) {
+        for (stage, systems) in &mut self.stages {
+            systems.run(world, *stage);
+        }
+
+        world.update_events();
+        world.clear_trackers();
+    }
+
+    pub fn run_stage(&mut self, world: &mut World, stage: Stage) {
+        if let Some(systems) = self.stages.get_mut(&stage) {
+            systems.run(world, stage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        any::type_name,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+
+    use rayon::ThreadPoolBuilder;
+
+    use super::*;
+    use crate::engine::ecs::component::Component;
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    impl Resource for Log {}
+
+    fn start(mut log: ResMut<Log>) {
+        log.0.push("start");
+    }
+
+    fn first(mut log: ResMut<Log>) {
+        log.0.push("first");
+    }
+
+    fn second(mut log: ResMut<Log>) {
+        log.0.push("second");
+    }
+
+    fn third(mut log: ResMut<Log>) {
+        log.0.push("third");
+    }
+
+    fn count_runs(mut log: ResMut<Log>, mut runs: Local<u32>) {
+        *runs += 1;
+        if *runs == 2 {
+            log.0.push("counted twice");
+        }
+    }
+
+    #[test]
+    fn systems_follow_stages_and_ordering() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, third.after("second"));
+        schedule.add_system(Stage::Update, second.after("first"));
+        schedule.add_system(Stage::PostUpdate, count_runs);
+        schedule.add_system(Stage::Update, first);
+        schedule.add_system(Stage::PreUpdate, start);
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+
+        let frame = ["start", "first", "second", "third"];
+        assert_eq!(
+            world.resource::<Log>().0,
+            [&frame[..], &frame[..], &["counted twice"]].concat()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn ordering_cycles_panic() {
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, first.after("third"));
+        schedule.add_system(Stage::Update, second.after("first"));
+        schedule.add_system(Stage::Update, third.after("second"));
+        schedule.run(&mut World::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown label")]
+    fn unknown_labels_panic() {
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, first.before("missing"));
+        schedule.run(&mut World::new());
+    }
+
+    #[derive(Default)]
+    struct Concurrency {
+        running: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl Resource for Concurrency {}
+
+    struct Health;
+
+    impl Component for Health {}
+
+    struct Position;
+
+    impl Component for Position {}
+
+    fn track(concurrency: &Concurrency) {
+        let running = concurrency.running.fetch_add(1, Ordering::SeqCst) + 1;
+        concurrency.max.fetch_max(running, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(50));
+        concurrency.running.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn read_health(_health: Query<&Health>, concurrency: Res<Concurrency>) {
+        track(&concurrency);
+    }
+
+    fn also_read_health(_health: Query<&Health>, concurrency: Res<Concurrency>) {
+        track(&concurrency);
+    }
+
+    fn write_position(_positions: Query<&mut Position>, concurrency: Res<Concurrency>) {
+        track(&concurrency);
+    }
+
+    fn write_health(_health: Query<&mut Health>, concurrency: Res<Concurrency>) {
+        track(&concurrency);
+    }
+
+    #[test]
+    fn compatible_systems_share_a_batch() {
+        let mut world = World::new();
+        world.init_resource::<Concurrency>();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, read_health);
+        schedule.add_system(Stage::Update, also_read_health);
+        schedule.add_system(Stage::Update, write_position);
+        schedule.add_system(Stage::Update, write_health.after("read_health"));
+
+        let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        pool.install(|| schedule.run(&mut world));
+
+        let concurrency = world.resource::<Concurrency>();
+        assert_eq!(concurrency.max.load(Ordering::SeqCst), 3);
+        assert_eq!(concurrency.running.load(Ordering::SeqCst), 0);
+
+        // only the unordered health reader and writer are reported
+        let ambiguities = schedule.get_stage(Stage::Update).unwrap().get_ambiguities();
+        assert_eq!(ambiguities.len(), 1);
+        assert_eq!(
+            (
+                ambiguities[0].first.as_str(),
+                ambiguities[0].second.as_str()
+            ),
+            ("also_read_health", "write_health")
+        );
+        assert_eq!(ambiguities[0].conflicts, vec![type_name::<Health>()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting access")]
+    fn aliasing_params_panic() {
+        fn alias(_writer: Query<&mut Health>, _reader: Query<&Health>) {}
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(Stage::Update, alias);
+        schedule.run(&mut World::new());
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        RwLock,
+    },
+};
+
+use super::{
+    component::{
+        Bundle, Component, ComponentRef, ComponentStorage, ErasedStorage, Mut, SystemTicks, Tick,
+    },
+    entity::{Entity, EntityAllocator},
+    event::{update_events, Event, Events},
+    hierarchy::{Children, Parent},
+    observer::{ComponentHooks, Lifecycle, ObserverKey, Observers},
+    query::{Query, QueryData, QueryFilter},
+    resource::{Res, ResMut, Resource},
+};
+
+pub struct World {
+    entities: EntityAllocator,
+    storages: HashMap<TypeId, Box<dyn ErasedStorage>>,
+    storage_order: Vec<TypeId>,
+    hooks: HashMap<TypeId, ComponentHooks>,
+    observers: Observers,
+    resources: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    event_updaters: Vec<fn(&World)>,
+    change_tick: AtomicU32,
+    last_change_tick: Tick,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            entities: EntityAllocator::new(),
+            storages: HashMap::new(),
+            storage_order: Vec::new(),
+            hooks: HashMap::new(),
+            observers: Observers::default(),
+            resources: HashMap::new(),
+            event_updaters: Vec::new(),
+            change_tick: AtomicU32::new(1),
+            last_change_tick: Tick::new(0),
+        }
+    }
+
+    pub fn get_change_tick(&self) -> Tick {
+        Tick::new(self.change_tick.load(Ordering::Acquire))
+    }
+
+    pub fn increment_change_tick(&self) -> Tick {
+        Tick::new(self.change_tick.fetch_add(1, Ordering::AcqRel))
+    }
+
+    pub fn get_last_change_tick(&self) -> Tick {
+        self.last_change_tick
+    }
+
+    pub fn get_ticks(&self) -> SystemTicks {
+        SystemTicks {
+            last_run: self.last_change_tick,
+            this_run: self.get_change_tick(),
+        }
+    }
+
+    pub fn clear_trackers(&mut self) {
+        self.last_change_tick = self.increment_change_tick();
+    }
+
+    pub fn spawn_empty(&mut self) -> Entity {
+        self.entities.spawn()
+    }
+
+    pub fn reserve_entity(&self) -> Entity {
+        self.entities.reserve()
+    }
+
+    pub fn flush_entities(&mut self) {
+        self.entities.flush();
+    }
+
+    pub fn spawn(&mut self, bundle: impl Bundle) -> Entity {
+        let entity = self.entities.spawn();
+        bundle.insert_into(self, entity);
+        entity
+    }
+
+    pub fn spawn_batch<B: Bundle>(&mut self, bundles: impl IntoIterator<Item = B>) -> Vec<Entity> {
+        let bundles = bundles.into_iter().collect::<Vec<_>>();
+        let entities = self.entities.spawn_batch(bundles.len());
+
+        for (entity, bundle) in entities.iter().zip(bundles) {
+            bundle.insert_into(self, *entity);
+        }
+
+        entities
+    }
+
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.entities.is_alive(entity) {
+            return false;
+        }
+
+        // the hierarchy must not point at dead entities, so orphaned children become roots
+        self.remove_parent(entity);
+        if let Some(children) = self.remove::<Children>(entity) {
+            for child in children.iter() {
+                self.remove::<Parent>(child);
+            }
+        }
+
+        let removed = self
+            .storage_order
+            .iter()
+            .filter(|type_id| self.storages[*type_id].contains_entity(entity))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for type_id in removed {
+            self.trigger_lifecycle(type_id, Lifecycle::Remove, entity);
+        }
+
+        self.observers.remove_entity(entity);
+
+        if !self.entities.despawn(entity) {
+            return false;
+        }
+
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(entity);
+        }
+        true
+    }
+
+    pub fn add_child(&mut self, parent: Entity, child: Entity) {
+        if !self.is_alive(parent) || !self.is_alive(child) {
+            panic!("Cannot attach {child:?} to {parent:?} because one of them is dead.");
+        }
+
+        if self.is_ancestor_of(child, parent) || child == parent {
+            panic!("Cannot attach {child:?} to {parent:?} because it would create a cycle.");
+        }
+
+        self.remove_parent(child);
+        self.insert(child, Parent::new(parent));
+
+        match self.get_mut::<Children>(parent) {
+            Some(mut children) => children.push(child),
+            None => {
+                let mut children = Children::default();
+                children.push(child);
+                self.insert(parent, children);
+            }
+        }
+    }
+
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let parent = self.remove::<Parent>(child)?.get();
+
+        if let Some(mut children) = self.get_mut::<Children>(parent) {
+            children.remove(child);
+        }
+
+        Some(parent)
+    }
+
+    pub fn is_ancestor_of(&self, ancestor: Entity, entity: Entity) -> bool {
+        let mut current = self.get::<Parent>(entity).map(|parent| parent.get());
+
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+
+            current = self.get::<Parent>(parent).map(|parent| parent.get());
+        }
+
+        false
+    }
+
+    pub fn despawn_recursive(&mut self, entity: Entity) -> bool {
+        self.remove_parent(entity);
+        self.despawn_with_children(entity)
+    }
+
+    fn despawn_with_children(&mut self, entity: Entity) -> bool {
+        if let Some(children) = self.remove::<Children>(entity) {
+            for child in children.iter() {
+                self.despawn_with_children(child);
+            }
+        }
+
+        self.despawn(entity)
+    }
+
+    pub fn despawn_batch(&mut self, entities: &[Entity]) -> usize {
+        entities
+            .iter()
+            .filter(|entity| self.despawn(**entity))
+            .count()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn get_entities(&self) -> &EntityAllocator {
+        &self.entities
+    }
+
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.entities.is_alive(entity) {
+            panic!("Cannot insert component into dead entity {entity:?}.");
+        }
+
+        let tick = self.get_change_tick();
+        let previous = self.get_storage_or_insert::<T>().insert(entity, component, tick);
+
+        if previous.is_none() {
+            self.trigger_lifecycle(TypeId::of::<T>(), Lifecycle::Add, entity);
+        }
+        self.trigger_lifecycle(TypeId::of::<T>(), Lifecycle::Insert, entity);
+
+        previous
+    }
+
+    pub fn insert_bundle(&mut self, entity: Entity, bundle: impl Bundle) {
+        if !self.entities.is_alive(entity) {
+            panic!("Cannot insert bundle into dead entity {entity:?}.");
+        }
+
+        bundle.insert_into(self, entity);
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        if self.has::<T>(entity) {
+            self.trigger_lifecycle(TypeId::of::<T>(), Lifecycle::Remove, entity);
+        }
+
+        self.storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<RwLock<ComponentStorage<T>>>()
+            .unwrap()
+            .get_mut()
+            .unwrap()
+            .remove(entity)
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<ComponentRef<'_, T>> {
+        let guard = self
+            .get_storage::<T>()?
+            .try_read()
+            .expect("Component storage is already mutably borrowed.");
+        let index = guard.get_dense_index(entity)?;
+
+        Some(ComponentRef::new(guard, index))
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<'_, T>> {
+        let ticks = self.get_ticks();
+        let (value, component_ticks) = self
+            .storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<RwLock<ComponentStorage<T>>>()
+            .unwrap()
+            .get_mut()
+            .unwrap()
+            .get_with_ticks_mut(entity)?;
+
+        Some(Mut::new(value, component_ticks, ticks))
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    pub fn query<Q: QueryData>(&self) -> Query<'_, Q> {
+        Query::new(self)
+    }
+
+    pub fn query_filtered<Q: QueryData, F: QueryFilter>(&self) -> Query<'_, Q, F> {
+        Query::new(self)
+    }
+
+    pub fn insert_resource<T: Resource>(&mut self, resource: T) -> Option<T> {
+        self.resources
+            .insert(TypeId::of::<T>(), Box::new(RwLock::new(resource)))
+            .map(|previous| {
+                previous
+                    .downcast::<RwLock<T>>()
+                    .unwrap()
+                    .into_inner()
+                    .unwrap()
+            })
+    }
+
+    pub fn init_resource<T: Resource + Default>(&mut self) {
+        if !self.contains_resource::<T>() {
+            self.insert_resource(T::default());
+        }
+    }
+
+    pub fn remove_resource<T: Resource>(&mut self) -> Option<T> {
+        self.resources
+            .remove(&TypeId::of::<T>())
+            .map(|resource| {
+                resource
+                    .downcast::<RwLock<T>>()
+                    .unwrap()
+                    .into_inner()
+                    .unwrap()
+            })
+    }
+
+    pub fn contains_resource<T: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn resource<T: Resource>(&self) -> Res<'_, T> {
+        self.get_resource::<T>()
+            .unwrap_or_else(|| panic!("Resource {} does not exist.", type_name::<T>()))
+    }
+
+    pub fn resource_mut<T: Resource>(&self) -> ResMut<'_, T> {
+        self.get_resource_mut::<T>()
+            .unwrap_or_else(|| panic!("Resource {} does not exist.", type_name::<T>()))
+    }
+
+    pub fn get_resource<T: Resource>(&self) -> Option<Res<'_, T>> {
+        self.get_resource_lock::<T>().map(|resource| {
+            Res::new(resource.try_read().unwrap_or_else(|_| {
+                panic!("Resource {} is already mutably borrowed.", type_name::<T>())
+            }))
+        })
+    }
+
+    pub fn get_resource_mut<T: Resource>(&self) -> Option<ResMut<'_, T>> {
+        self.get_resource_lock::<T>().map(|resource| {
+            ResMut::new(resource.try_write().unwrap_or_else(|_| {
+                panic!("Resource {} is already borrowed.", type_name::<T>())
+            }))
+        })
+    }
+
+    pub fn add_event<T: Event>(&mut self) {
+        if !self.contains_resource::<Events<T>>() {
+            self.insert_resource(Events::<T>::new());
+            self.event_updaters.push(update_events::<T>);
+        }
+    }
+
+    pub fn send_event<T: Event>(&self, event: T) {
+        self.get_resource_mut::<Events<T>>()
+            .unwrap_or_else(|| panic!("Event {} was not added to the world.", type_name::<T>()))
+            .send(event);
+    }
+
+    pub fn update_events(&self) {
+        for updater in &self.event_updaters {
+            updater(self);
+        }
+    }
+
+    pub fn register_component_hooks<T: Component>(&mut self) -> &mut ComponentHooks {
+        self.hooks.entry(TypeId::of::<T>()).or_default()
+    }
+
+    pub fn observe<T: Component>(
+        &mut self,
+        entity: Entity,
+        lifecycle: Lifecycle,
+        mut observer: impl FnMut(&mut World, Entity) + Send + Sync + 'static,
+    ) {
+        self.observers.add(
+            entity,
+            ObserverKey::Component(TypeId::of::<T>(), lifecycle),
+            Box::new(move |world, entity, _| observer(world, entity)),
+        );
+    }
+
+    pub fn observe_event<E: Event>(
+        &mut self,
+        entity: Entity,
+        mut observer: impl FnMut(&mut World, Entity, &E) + Send + Sync + 'static,
+    ) {
+        self.observers.add(
+            entity,
+            ObserverKey::Event(TypeId::of::<E>()),
+            Box::new(move |world, entity, event| {
+                observer(world, entity, event.downcast_ref().unwrap())
+            }),
+        );
+    }
+
+    pub fn trigger<E: Event>(&mut self, entity: Entity, event: E) {
+        self.run_observers(entity, ObserverKey::Event(TypeId::of::<E>()), &event);
+    }
+
+    fn trigger_lifecycle(&mut self, type_id: TypeId, lifecycle: Lifecycle, entity: Entity) {
+        if let Some(hook) = self.hooks.get(&type_id).and_then(|hooks| hooks.get(lifecycle)) {
+            hook(self, entity);
+        }
+
+        self.run_observers(entity, ObserverKey::Component(type_id, lifecycle), &());
+    }
+
+    fn run_observers(&mut self, entity: Entity, key: ObserverKey, event: &dyn Any) {
+        let Some(mut observers) = self.observers.take(entity, key) else {
+            return;
+        };
+
+        for observer in &mut observers {
+            observer(self, entity, event);
+        }
+
+        if self.is_alive(entity) {
+            self.observers.restore(entity, key, observers);
+        }
+    }
+
+    fn get_resource_lock<T: Resource>(&self) -> Option<&RwLock<T>> {
+        self.resources
+            .get(&TypeId::of::<T>())
+            .map(|resource| resource.downcast_ref().unwrap())
+    }
+
+    pub(crate) fn get_storage<T: Component>(&self) -> Option<&RwLock<ComponentStorage<T>>> {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|storage| storage.as_any().downcast_ref().unwrap())
+    }
+
+    fn get_storage_or_insert<T: Component>(&mut self) -> &mut ComponentStorage<T> {
+        let storage_order = &mut self.storage_order;
+
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| {
+                storage_order.push(TypeId::of::<T>());
+                Box::new(RwLock::new(ComponentStorage::<T>::new()))
+            })
+            .as_any_mut()
+            .downcast_mut::<RwLock<ComponentStorage<T>>>()
+            .unwrap()
+            .get_mut()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn despawn_keeps_hierarchy_consistent() {
+        let mut world = World::new();
+        let root = world.spawn_empty();
+        let parent = world.spawn_empty();
+        let child = world.spawn_empty();
+        world.add_child(root, parent);
+        world.add_child(parent, child);
+
+        assert!(world.despawn(parent));
+        assert!(world.get::<Children>(root).unwrap().is_empty());
+        assert!(world.get::<Parent>(child).is_none());
+
+        let other = world.spawn_empty();
+        world.add_child(root, other);
+        world.add_child(other, child);
+        assert!(world.despawn_recursive(other));
+        assert!(!world.is_alive(child));
+        assert!(world.get::<Children>(root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn components_follow_entity_generations() {
+        #[derive(Debug, PartialEq)]
+        struct Health(i32);
+
+        impl Component for Health {}
+
+        let mut world = World::new();
+        let a = world.spawn(Health(1));
+        let b = world.spawn(Health(2));
+        assert!(world.despawn(a));
+        assert!(world.get::<Health>(a).is_none());
+
+        let c = world.spawn(Health(3));
+        assert_eq!(c.get_index(), a.get_index());
+        assert!(world.get::<Health>(a).is_none());
+        assert_eq!(*world.get::<Health>(b).unwrap(), Health(2));
+        assert_eq!(*world.get::<Health>(c).unwrap(), Health(3));
+
+        assert_eq!(world.remove::<Health>(b), Some(Health(2)));
+        assert_eq!(*world.get::<Health>(c).unwrap(), Health(3));
+        assert_eq!(world.len(), 2);
+    }
+}
@@ -0,0 +1,299 @@
+use cgmath::{InnerSpace, Matrix4, Vector3, Vector4};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vector3<f32>, distance: f32) -> Self {
+        Self { normal, distance }
+    }
+
+    // planes without a direction (e.g. the far plane of an infinite projection) contain everything
+    pub fn from_coefficients(coefficients: Vector4<f32>) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.magnitude();
+
+        if length <= f32::EPSILON {
+            return Self::new(Vector3::new(0.0, 0.0, 0.0), f32::INFINITY);
+        }
+
+        Self::new(normal / length, coefficients.w / length)
+    }
+
+    pub fn get_signed_distance(&self, point: Vector3<f32>) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn get_point(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denominator = plane.normal.dot(self.direction);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let distance = -plane.get_signed_distance(self.origin) / denominator;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vector3<f32>, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Self {
+        let scale = matrix
+            .x
+            .truncate()
+            .magnitude()
+            .max(matrix.y.truncate().magnitude())
+            .max(matrix.z.truncate().magnitude());
+
+        Self::new(
+            (matrix * self.center.extend(1.0)).truncate(),
+            self.radius * scale,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = Vector3<f32>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::new(first, first), |bounds, point| {
+            Self::new(
+                Vector3::new(
+                    bounds.min.x.min(point.x),
+                    bounds.min.y.min(point.y),
+                    bounds.min.z.min(point.z),
+                ),
+                Vector3::new(
+                    bounds.max.x.max(point.x),
+                    bounds.max.y.max(point.y),
+                    bounds.max.z.max(point.z),
+                ),
+            )
+        }))
+    }
+
+    pub fn get_center(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn get_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn get_bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(self.get_center(), self.get_extents().magnitude())
+    }
+
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Self {
+        let center = (matrix * self.get_center().extend(1.0)).truncate();
+        let extents = self.get_extents();
+
+        let extents = Vector3::new(
+            matrix.x.x.abs() * extents.x
+                + matrix.y.x.abs() * extents.y
+                + matrix.z.x.abs() * extents.z,
+            matrix.x.y.abs() * extents.x
+                + matrix.y.y.abs() * extents.y
+                + matrix.z.y.abs() * extents.z,
+            matrix.x.z.abs() * extents.x
+                + matrix.y.z.abs() * extents.y
+                + matrix.z.z.abs() * extents.z,
+        );
+
+        Self::new(center - extents, center + extents)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Plane; 6],
+}
+
+impl Frustum {
+    // clip space is x, y in [-w, w] and z in [0, w], so this holds for standard and reverse depth
+    pub fn from_matrix(view_projection: Matrix4<f32>) -> Self {
+        let row = |index: usize| {
+            Vector4::new(
+                view_projection.x[index],
+                view_projection.y[index],
+                view_projection.z[index],
+                view_projection.w[index],
+            )
+        };
+
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        Self {
+            planes: [
+                Plane::from_coefficients(w + x),
+                Plane::from_coefficients(w - x),
+                Plane::from_coefficients(w + y),
+                Plane::from_coefficients(w - y),
+                Plane::from_coefficients(z),
+                Plane::from_coefficients(w - z),
+            ],
+        }
+    }
+
+    pub fn get_planes(&self) -> &[Plane; 6] {
+        &self.planes
+    }
+
+    pub fn contains_point(&self, point: Vector3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.get_signed_distance(point) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.get_signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.get_center();
+        let extents = aabb.get_extents();
+
+        self.planes.iter().all(|plane| {
+            let radius = extents.x * plane.normal.x.abs()
+                + extents.y * plane.normal.y.abs()
+                + extents.z * plane.normal.z.abs();
+
+            plane.get_signed_distance(center) >= -radius
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, One, Quaternion, Rotation3, SquareMatrix};
+
+    use super::*;
+    use crate::engine::camera::StarryCamera;
+
+    fn is_close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 1e-5
+    }
+
+    fn create_camera() -> StarryCamera {
+        let mut camera = StarryCamera::new();
+        camera.set_perspective_projection(90.0, 1.0, 0.1, 100.0);
+        camera.set_view_rotation(Vector3::new(0.0, 0.0, 0.0), Quaternion::one());
+        camera
+    }
+
+    fn unit_box_at(x: f32, y: f32, z: f32) -> Aabb {
+        Aabb::new(Vector3::new(-0.5, -0.5, -0.5), Vector3::new(0.5, 0.5, 0.5))
+            .transform(&Matrix4::from_translation(Vector3::new(x, y, z)))
+    }
+
+    #[test]
+    fn planes_match_clip_space_bounds() {
+        let planes = *Frustum::from_matrix(Matrix4::identity()).get_planes();
+        let expected = [
+            Plane::new(Vector3::new(1.0, 0.0, 0.0), 1.0),
+            Plane::new(Vector3::new(-1.0, 0.0, 0.0), 1.0),
+            Plane::new(Vector3::new(0.0, 1.0, 0.0), 1.0),
+            Plane::new(Vector3::new(0.0, -1.0, 0.0), 1.0),
+            Plane::new(Vector3::new(0.0, 0.0, 1.0), 0.0),
+            Plane::new(Vector3::new(0.0, 0.0, -1.0), 1.0),
+        ];
+        assert_eq!(planes, expected);
+
+        let planes = *create_camera().get_frustum().get_planes();
+        assert!(is_close(
+            planes[0].normal,
+            Vector3::new(1.0, 0.0, 1.0).normalize()
+        ));
+        assert!(is_close(planes[4].normal, Vector3::new(0.0, 0.0, 1.0)));
+        assert!((planes[4].distance + 0.1).abs() < 1e-5);
+        assert!(is_close(planes[5].normal, Vector3::new(0.0, 0.0, -1.0)));
+        assert!((planes[5].distance - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn boxes_and_spheres_are_culled_outside_the_planes() {
+        let frustum = create_camera().get_frustum();
+
+        assert!(frustum.intersects_aabb(&unit_box_at(0.0, 0.0, 5.0)));
+        assert!(!frustum.intersects_aabb(&unit_box_at(0.0, 0.0, -0.6)));
+        assert!(!frustum.intersects_aabb(&unit_box_at(8.0, 0.0, 5.0)));
+        assert!(frustum.intersects_aabb(&unit_box_at(5.3, 0.0, 5.0)));
+        assert!(!frustum.intersects_aabb(&unit_box_at(0.0, -8.0, 5.0)));
+        assert!(!frustum.intersects_aabb(&unit_box_at(0.0, 0.0, 200.0)));
+        assert!(frustum.intersects_aabb(&unit_box_at(0.0, 0.0, 100.3)));
+
+        assert!(frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(0.0, 0.0, 0.0), 0.2)));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(0.0, 0.0, -0.5), 0.2)));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(8.0, 0.0, 5.0), 1.0)));
+
+        assert!(frustum.contains_point(Vector3::new(0.0, 0.0, 1.0)));
+        assert!(!frustum.contains_point(Vector3::new(0.0, 0.0, 0.05)));
+    }
+
+    #[test]
+    fn bounds_follow_their_transform() {
+        let unit = Aabb::new(Vector3::new(-0.5, -0.5, -0.5), Vector3::new(0.5, 0.5, 0.5));
+        let rotated =
+            unit.transform(&(Matrix4::from_angle_y(Deg(45.0)) * Matrix4::from_scale(2.0)));
+        assert!((rotated.max.x - 2.0f32.sqrt()).abs() < 1e-5);
+        assert!((rotated.max.y - 1.0).abs() < 1e-5);
+
+        let sphere = BoundingSphere::new(Vector3::new(1.0, 0.0, 0.0), 1.0)
+            .transform(&Matrix4::from_nonuniform_scale(1.0, 3.0, 2.0));
+        assert_eq!(sphere.radius, 3.0);
+        assert_eq!(Aabb::from_points(Vec::new()), None);
+
+        let mut camera = create_camera();
+        camera.set_view_rotation(
+            Vector3::new(0.0, 0.0, 0.0),
+            Quaternion::from_angle_y(Deg(180.0)),
+        );
+        let frustum = camera.get_frustum();
+        assert!(frustum.intersects_aabb(&unit_box_at(0.0, 0.0, -5.0)));
+        assert!(!frustum.intersects_aabb(&unit_box_at(0.0, 0.0, 5.0)));
+    }
+}
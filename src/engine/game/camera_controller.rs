@@ -0,0 +1,373 @@
+use cgmath::{InnerSpace, Rotation, Vector2, Vector3, Zero};
+
+use crate::{
+    app::{App, Plugin},
+    engine::{
+        camera::{DepthMode, StarryCamera},
+        ecs::{
+            component::Component,
+            entity::Entity,
+            query::Query,
+            resource::{Res, ResMut},
+            system::{IntoSystemDescriptor, Stage},
+        },
+        input::{
+            action::ActionState,
+            mouse::{MouseLook, MouseMotion},
+        },
+        time::Time,
+        window::StarryWindow,
+    },
+};
+
+use super::{
+    game_object::{euler_to_quaternion, TransformComponent},
+    global_transform::GlobalTransform,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveCamera {
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+    pub depth_mode: DepthMode,
+}
+
+impl Component for ActiveCamera {}
+
+impl Default for ActiveCamera {
+    fn default() -> Self {
+        Self {
+            fov_y: 50.0,
+            near: 0.1,
+            far: 100.0,
+            depth_mode: DepthMode::Standard,
+        }
+    }
+}
+
+impl ActiveCamera {
+    pub fn new(fov_y: f32, near: f32, far: f32) -> Self {
+        Self {
+            fov_y,
+            near,
+            far,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_depth_mode(mut self, depth_mode: DepthMode) -> Self {
+        self.depth_mode = depth_mode;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FlyCamera {
+    pub move_speed: f32,
+    pub look_speed: f32,
+}
+
+impl Component for FlyCamera {}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        Self {
+            move_speed: 5.0,
+            look_speed: 180.0,
+        }
+    }
+}
+
+impl FlyCamera {
+    pub fn new(move_speed: f32, look_speed: f32) -> Self {
+        Self {
+            move_speed,
+            look_speed,
+        }
+    }
+
+    pub fn update(
+        &self,
+        transform: &mut TransformComponent,
+        dt: f32,
+        look: Vector2<f32>,
+        movement: Vector3<f32>,
+        max_pitch: f32,
+    ) {
+        transform.rotate_yaw_pitch(look.x, look.y, max_pitch);
+
+        let up_dir = Vector3::new(0.0, -1.0, 0.0);
+        let move_dir = transform.get_right() * movement.x
+            + up_dir * movement.y
+            + transform.get_forward() * movement.z;
+
+        let move_dir = if move_dir.magnitude2() > 1.0 {
+            move_dir.normalize()
+        } else {
+            move_dir
+        };
+
+        transform.translation += self.move_speed * dt * move_dir;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitCamera {
+    pub target: Vector3<f32>,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub zoom_speed: f32,
+    pub pan_speed: f32,
+    pub look_speed: f32,
+}
+
+impl Component for OrbitCamera {}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: Vector3::zero(),
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            min_distance: 0.5,
+            max_distance: 50.0,
+            zoom_speed: 0.1,
+            pan_speed: 1.0,
+            look_speed: 180.0,
+        }
+    }
+}
+
+impl OrbitCamera {
+    pub fn new(target: Vector3<f32>, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            ..Default::default()
+        }
+    }
+
+    pub fn rotate(&mut self, yaw: f32, pitch: f32, max_pitch: f32) {
+        self.yaw = (self.yaw + yaw) % 360.0;
+        self.pitch = (self.pitch + pitch).clamp(-max_pitch, max_pitch);
+    }
+
+    // scroll is in lines, each line moves zoom_speed of the current distance
+    pub fn zoom(&mut self, scroll: f32) {
+        self.distance = (self.distance * (1.0 - scroll * self.zoom_speed))
+            .clamp(self.min_distance, self.max_distance);
+    }
+
+    pub fn pan(&mut self, dt: f32, pan: Vector2<f32>) {
+        let rotation = euler_to_quaternion(Vector3::new(self.pitch, self.yaw, 0.0));
+        let right = rotation.rotate_vector(Vector3::unit_x());
+        let up = -rotation.rotate_vector(Vector3::unit_y());
+
+        self.target += (right * pan.x + up * pan.y) * self.pan_speed * self.distance * dt;
+    }
+
+    pub fn apply(&self, transform: &mut TransformComponent) {
+        transform.set_euler_rotation(Vector3::new(self.pitch, self.yaw, 0.0));
+        transform.translation = self.target - transform.get_forward() * self.distance;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FollowCamera {
+    pub target: Entity,
+    pub pivot_offset: Vector3<f32>,
+    pub arm_length: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub stiffness: f32,
+    pub look_speed: f32,
+}
+
+impl Component for FollowCamera {}
+
+impl FollowCamera {
+    pub fn new(target: Entity, arm_length: f32) -> Self {
+        Self {
+            target,
+            pivot_offset: Vector3::new(0.0, -1.0, 0.0),
+            arm_length,
+            yaw: 0.0,
+            pitch: -20.0,
+            stiffness: 8.0,
+            look_speed: 180.0,
+        }
+    }
+
+    pub fn rotate(&mut self, yaw: f32, pitch: f32, max_pitch: f32) {
+        self.yaw = (self.yaw + yaw) % 360.0;
+        self.pitch = (self.pitch + pitch).clamp(-max_pitch, max_pitch);
+    }
+
+    pub fn get_pivot(&self, target_position: Vector3<f32>) -> Vector3<f32> {
+        target_position + self.pivot_offset
+    }
+
+    pub fn get_desired_position(&self, target_position: Vector3<f32>) -> Vector3<f32> {
+        let forward = euler_to_quaternion(Vector3::new(self.pitch, self.yaw, 0.0))
+            .rotate_vector(Vector3::unit_z());
+
+        self.get_pivot(target_position) - forward * self.arm_length
+    }
+
+    pub fn update(
+        &self,
+        transform: &mut TransformComponent,
+        dt: f32,
+        target_position: Vector3<f32>,
+    ) {
+        let desired = self.get_desired_position(target_position);
+        let blend = 1.0 - (-self.stiffness * dt).exp();
+
+        transform.translation += (desired - transform.translation) * blend;
+        transform.look_at(
+            self.get_pivot(target_position),
+            Vector3::new(0.0, -1.0, 0.0),
+        );
+    }
+}
+
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Stage::Update, update_fly_cameras.label("fly_camera"))
+            .add_system(
+                Stage::Update,
+                update_orbit_cameras
+                    .label("orbit_camera")
+                    .after("fly_camera"),
+            )
+            .add_system(
+                Stage::Update,
+                update_follow_cameras
+                    .label("follow_camera")
+                    .after("orbit_camera"),
+            )
+            .add_system(
+                Stage::PostUpdate,
+                update_active_camera
+                    .label("active_camera")
+                    .after("propagate_transforms"),
+            );
+    }
+}
+
+fn get_look_input(
+    actions: &ActionState,
+    mouse_look: &MouseLook,
+    mouse_motion: &MouseMotion,
+    look_speed: f32,
+    dt: f32,
+) -> Vector2<f32> {
+    let axes = Vector2::new(actions.get_axis("look_yaw"), actions.get_axis("look_pitch"));
+    let axes = if axes.magnitude2() > 1.0 {
+        axes.normalize()
+    } else {
+        axes
+    };
+
+    axes * look_speed * dt + mouse_look.get_look_delta(mouse_motion)
+}
+
+pub fn update_fly_cameras(
+    actions: Res<ActionState>,
+    mouse_look: Res<MouseLook>,
+    mouse_motion: Res<MouseMotion>,
+    time: Res<Time>,
+    mut cameras: Query<(&FlyCamera, &mut TransformComponent)>,
+) {
+    let dt = time.get_delta_seconds();
+    let movement = Vector3::new(
+        actions.get_axis("move_right"),
+        actions.get_axis("move_up"),
+        actions.get_axis("move_forward"),
+    );
+
+    for (camera, mut transform) in cameras.iter_mut() {
+        let look = get_look_input(&actions, &mouse_look, &mouse_motion, camera.look_speed, dt);
+        camera.update(&mut transform, dt, look, movement, mouse_look.max_pitch);
+    }
+}
+
+pub fn update_orbit_cameras(
+    actions: Res<ActionState>,
+    mouse_look: Res<MouseLook>,
+    mouse_motion: Res<MouseMotion>,
+    time: Res<Time>,
+    mut cameras: Query<(&mut OrbitCamera, &mut TransformComponent)>,
+) {
+    let dt = time.get_delta_seconds();
+    let pan = Vector2::new(actions.get_axis("move_right"), actions.get_axis("move_up"));
+
+    for (mut camera, mut transform) in cameras.iter_mut() {
+        let look = get_look_input(&actions, &mouse_look, &mouse_motion, camera.look_speed, dt);
+
+        camera.rotate(look.x, look.y, mouse_look.max_pitch);
+        camera.zoom(mouse_motion.get_scroll());
+        camera.pan(dt, pan);
+        camera.apply(&mut transform);
+    }
+}
+
+pub fn update_follow_cameras(
+    actions: Res<ActionState>,
+    mouse_look: Res<MouseLook>,
+    mouse_motion: Res<MouseMotion>,
+    time: Res<Time>,
+    mut cameras: Query<(&mut FollowCamera, &mut TransformComponent)>,
+    targets: Query<&GlobalTransform>,
+) {
+    let dt = time.get_delta_seconds();
+
+    for (mut camera, mut transform) in cameras.iter_mut() {
+        let target_position = match targets.get(camera.target) {
+            Some(target) => target.get_translation(),
+            None => continue,
+        };
+
+        let look = get_look_input(&actions, &mouse_look, &mouse_motion, camera.look_speed, dt);
+        camera.rotate(look.x, look.y, mouse_look.max_pitch);
+        camera.update(&mut transform, dt, target_position);
+    }
+}
+
+pub fn update_active_camera(
+    mut camera: ResMut<StarryCamera>,
+    window: Option<Res<StarryWindow>>,
+    cameras: Query<(&ActiveCamera, &GlobalTransform)>,
+) {
+    let (active, global_transform) = match cameras.iter().next() {
+        Some(active) => active,
+        None => return,
+    };
+
+    let aspect = window.map_or(1.0, |window| window.get_aspect());
+    match active.depth_mode {
+        DepthMode::Standard => {
+            camera.set_perspective_projection(active.fov_y, aspect, active.near, active.far)
+        }
+        DepthMode::ReverseZ => camera.set_reverse_z_perspective_projection(
+            active.fov_y,
+            aspect,
+            active.near,
+            active.far,
+        ),
+        DepthMode::InfiniteReverseZ => {
+            camera.set_infinite_reverse_z_perspective_projection(active.fov_y, aspect, active.near)
+        }
+    }
+
+    // the camera may be parented, so the view comes from its world transform
+    let transform = TransformComponent::from_matrix(global_transform.get_matrix());
+    camera.set_view_rotation(transform.translation, transform.rotation);
+}
@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use cgmath::{InnerSpace, VectorSpace};
+
+use crate::engine::{
+    ecs::{
+        component::Component,
+        resource::Resource,
+        system::{Schedule, Stage},
+        world::World,
+    },
+    time::Time,
+};
+
+use super::game_object::TransformComponent;
+
+pub struct FixedTime {
+    step: Duration,
+    accumulator: Duration,
+    max_steps_per_frame: u32,
+    steps_this_frame: u32,
+    dropped_steps: u64,
+    alpha: f32,
+}
+
+impl Resource for FixedTime {}
+
+impl Default for FixedTime {
+    fn default() -> Self {
+        Self::new(60.0)
+    }
+}
+
+impl FixedTime {
+    pub fn new(rate: f64) -> Self {
+        let mut fixed_time = Self {
+            step: Duration::ZERO,
+            accumulator: Duration::ZERO,
+            max_steps_per_frame: 8,
+            steps_this_frame: 0,
+            dropped_steps: 0,
+            alpha: 0.0,
+        };
+
+        fixed_time.set_rate(rate);
+        fixed_time
+    }
+
+    pub fn set_rate(&mut self, rate: f64) {
+        if rate <= 0.0 {
+            panic!("Fixed update rate must be positive.");
+        }
+
+        self.step = Duration::from_secs_f64(1.0 / rate);
+    }
+
+    pub fn set_step(&mut self, step: Duration) {
+        if step.is_zero() {
+            panic!("Fixed update step must be positive.");
+        }
+
+        self.step = step;
+    }
+
+    pub fn get_rate(&self) -> f64 {
+        1.0 / self.step.as_secs_f64()
+    }
+
+    pub fn get_step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn get_step_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    pub fn set_max_steps_per_frame(&mut self, max_steps_per_frame: u32) {
+        self.max_steps_per_frame = max_steps_per_frame.max(1);
+    }
+
+    pub fn get_max_steps_per_frame(&self) -> u32 {
+        self.max_steps_per_frame
+    }
+
+    pub fn get_steps_this_frame(&self) -> u32 {
+        self.steps_this_frame
+    }
+
+    pub fn get_dropped_steps(&self) -> u64 {
+        self.dropped_steps
+    }
+
+    pub fn get_accumulator(&self) -> Duration {
+        self.accumulator
+    }
+
+    pub fn get_alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    pub fn accumulate(&mut self, delta: Duration) {
+        self.accumulator += delta;
+        self.steps_this_frame = 0;
+    }
+
+    pub fn expend(&mut self) -> bool {
+        if self.accumulator < self.step {
+            return false;
+        }
+
+        if self.steps_this_frame >= self.max_steps_per_frame {
+            let dropped = self.accumulator.as_nanos() / self.step.as_nanos();
+            self.dropped_steps += dropped as u64;
+            self.accumulator -= self.step * dropped as u32;
+            return false;
+        }
+
+        self.accumulator -= self.step;
+        self.steps_this_frame += 1;
+        true
+    }
+
+    fn update_alpha(&mut self) {
+        self.alpha = self.accumulator.as_secs_f32() / self.step.as_secs_f32();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterpolatedTransform {
+    previous: Option<TransformComponent>,
+}
+
+impl Component for InterpolatedTransform {}
+
+impl InterpolatedTransform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_previous(&self) -> Option<&TransformComponent> {
+        self.previous.as_ref()
+    }
+
+    pub fn interpolate(&self, current: &TransformComponent, alpha: f32) -> TransformComponent {
+        match &self.previous {
+            Some(previous) => interpolate_transforms(previous, current, alpha),
+            None => *current,
+        }
+    }
+}
+
+pub fn interpolate_transforms(
+    previous: &TransformComponent,
+    current: &TransformComponent,
+    alpha: f32,
+) -> TransformComponent {
+    let alpha = alpha.clamp(0.0, 1.0);
+
+    TransformComponent {
+        translation: previous.translation.lerp(current.translation, alpha),
+        scale: previous.scale.lerp(current.scale, alpha),
+        rotation: previous.rotation.nlerp(current.rotation, alpha).normalize(),
+    }
+}
+
+pub struct StarryGameLoop {
+    schedule: Schedule,
+}
+
+impl StarryGameLoop {
+    pub fn new(schedule: Schedule) -> Self {
+        Self { schedule }
+    }
+
+    pub fn get_schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    pub fn get_schedule_mut(&mut self) -> &mut Schedule {
+        &mut self.schedule
+    }
+
+    pub fn run_frame(&mut self, world: &mut World) {
+        world.init_resource::<Time>();
+        world.init_resource::<FixedTime>();
+        world.resource_mut::<Time>().update();
+        let delta = world.resource::<Time>().get_delta();
+
+        self.run_frame_with_delta(world, delta);
+    }
+
+    pub fn run_frame_with_delta(&mut self, world: &mut World, delta: Duration) {
+        world.init_resource::<FixedTime>();
+        world.resource_mut::<FixedTime>().accumulate(delta);
+
+        self.schedule.run_stage(world, Stage::PreUpdate);
+
+        while world.resource_mut::<FixedTime>().expend() {
+            store_previous_transforms(world);
+            self.schedule.run_stage(world, Stage::FixedUpdate);
+        }
+
+        world.resource_mut::<FixedTime>().update_alpha();
+
+        self.schedule.run_stage(world, Stage::Update);
+        self.schedule.run_stage(world, Stage::PostUpdate);
+        self.schedule.run_stage(world, Stage::Render);
+
+        world.update_events();
+        world.clear_trackers();
+    }
+}
+
+fn store_previous_transforms(world: &mut World) {
+    for (transform, mut interpolated) in world
+        .query::<(&TransformComponent, &mut InterpolatedTransform)>()
+        .iter_mut()
+    {
+        interpolated.previous = Some(*transform);
+    }
+}
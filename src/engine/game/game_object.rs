@@ -1,95 +1,259 @@
-use cgmath::{Deg, Vector3, Matrix4, Zero, Angle, prelude::InnerSpace};
-use winit::event::{KeyboardInput, VirtualKeyCode};
+use std::any::{type_name, Any};
 
-use crate::engine::resources::model::StarryModel;
+use cgmath::{
+    Deg, InnerSpace, Matrix3, Matrix4, One, Quaternion, Rad, Rotation, Rotation3, SquareMatrix,
+    Vector3,
+};
 
-static mut CURRENT_ID: u32 = 0;
+use crate::engine::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        reflect::{FieldInfo, Reflect},
+        world::World,
+    },
+    resources::model::StarryModel,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct TransformComponent {
     pub translation: Vector3<f32>,
     pub scale: Vector3<f32>,
-    pub rotation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
 }
 
-impl TransformComponent {
-    pub fn get_transform_matrix(&self) -> Matrix4<f32> {
-        let translation_matrix = Matrix4::from_translation(self.translation);
+impl Component for TransformComponent {}
 
-        let x_rotation_matrix = Matrix4::from_angle_x(Deg(self.rotation.x));
-        let y_rotation_matrix = Matrix4::from_angle_y(Deg(self.rotation.y));
-        let z_rotation_matrix = Matrix4::from_angle_z(Deg(self.rotation.z));
+// rotation is reflected as euler degrees so scenes stay readable; the quaternion never leaves memory
+impl Reflect for TransformComponent {
+    fn get_field_infos() -> Vec<FieldInfo> {
+        ["translation", "scale", "rotation"]
+            .into_iter()
+            .map(|name| FieldInfo {
+                name,
+                type_name: type_name::<Vector3<f32>>(),
+            })
+            .collect()
+    }
 
-        let scale_matrix = Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z);
-        let transform_matrix = translation_matrix * y_rotation_matrix * x_rotation_matrix * z_rotation_matrix * scale_matrix;
-        transform_matrix
+    fn get_field_names(&self) -> Vec<&'static str> {
+        vec!["translation", "scale", "rotation"]
+    }
+
+    fn get_field(&self, name: &str) -> Option<&dyn Reflect> {
+        match name {
+            "translation" => Some(&self.translation),
+            "scale" => Some(&self.scale),
+            _ => None,
+        }
+    }
+
+    fn get_field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        match name {
+            "translation" => Some(&mut self.translation),
+            "scale" => Some(&mut self.scale),
+            _ => None,
+        }
+    }
+
+    fn with_field(&self, name: &str, function: &mut dyn FnMut(&dyn Reflect)) -> bool {
+        match name {
+            "rotation" => {
+                function(&self.get_reflected_rotation());
+                true
+            }
+
+            _ => match self.get_field(name) {
+                Some(field) => {
+                    function(field);
+                    true
+                }
+
+                None => false,
+            },
+        }
+    }
+
+    fn with_field_mut(&mut self, name: &str, function: &mut dyn FnMut(&mut dyn Reflect)) -> bool {
+        match name {
+            "rotation" => {
+                let mut rotation = self.get_reflected_rotation();
+                function(&mut rotation);
+                self.set_euler_rotation(rotation);
+                true
+            }
+
+            _ => match self.get_field_mut(name) {
+                Some(field) => {
+                    function(field);
+                    true
+                }
+
+                None => false,
+            },
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
     }
 }
 
-pub struct StarryGameObject {
-    id: u32,
-    pub model: StarryModel,
-    pub transform: TransformComponent,
+impl Default for TransformComponent {
+    fn default() -> Self {
+        Self {
+            translation: Vector3 { x: 0.0, y: 0.0, z: 0.0 },
+            scale: Vector3 { x: 1.0, y: 1.0, z: 1.0 },
+            rotation: Quaternion::one(),
+        }
+    }
 }
 
-impl StarryGameObject {
-    fn new(id: u32, model: StarryModel) -> Self {
+impl TransformComponent {
+    pub fn from_euler(
+        translation: Vector3<f32>,
+        scale: Vector3<f32>,
+        rotation: Vector3<f32>,
+    ) -> Self {
         Self {
-            id,
-            model,
-            transform: TransformComponent {
-                translation: Vector3 { x: 0.0, y: 0.0, z: 0.0 },
-                scale: Vector3 { x: 1.0, y: 1.0, z: 1.0 },
-                rotation: Vector3 { x: 0.0, y: 0.0, z: 0.0 },
-            },
+            translation,
+            scale,
+            rotation: euler_to_quaternion(rotation),
         }
     }
 
-    fn new_with_transform(id: u32, model: StarryModel, transform: TransformComponent) -> Self {
+    pub fn from_matrix(matrix: Matrix4<f32>) -> Self {
+        let mut scale = Vector3 {
+            x: matrix.x.truncate().magnitude(),
+            y: matrix.y.truncate().magnitude(),
+            z: matrix.z.truncate().magnitude(),
+        };
+
+        if matrix.determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        let rotation_matrix = Matrix3::from_cols(
+            matrix.x.truncate() / scale.x,
+            matrix.y.truncate() / scale.y,
+            matrix.z.truncate() / scale.z,
+        );
+
         Self {
-            id,
-            model,
-            transform,
+            translation: matrix.w.truncate(),
+            scale,
+            rotation: Quaternion::from(rotation_matrix).normalize(),
         }
     }
 
-    pub fn create_new_game_object(model: StarryModel) -> Self {
-        unsafe {
-            CURRENT_ID += 1;
-            StarryGameObject::new(CURRENT_ID, model)
+    pub fn get_transform_matrix(&self) -> Matrix4<f32> {
+        let translation_matrix = Matrix4::from_translation(self.translation);
+        let rotation_matrix = Matrix4::from(self.rotation);
+        let scale_matrix = Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z);
+
+        translation_matrix * rotation_matrix * scale_matrix
+    }
+
+    pub fn get_euler_rotation(&self) -> Vector3<f32> {
+        let rotation_matrix = Matrix3::from(self.rotation);
+        let (x_axis, y_axis, z_axis) = (rotation_matrix.x, rotation_matrix.y, rotation_matrix.z);
+
+        let cos_pitch = z_axis.x.hypot(z_axis.z);
+        let pitch = (-z_axis.y).atan2(cos_pitch);
+
+        // looking straight up or down, yaw and roll share an axis so all of it goes into roll
+        let (yaw, roll) = if cos_pitch < 1e-4 {
+            (0.0, (pitch.signum() * x_axis.z).atan2(x_axis.x))
+        } else {
+            (z_axis.x.atan2(z_axis.z), x_axis.y.atan2(y_axis.y))
+        };
+
+        Vector3 {
+            x: Deg::from(Rad(pitch)).0,
+            y: Deg::from(Rad(yaw)).0,
+            z: Deg::from(Rad(roll)).0,
         }
     }
 
-    pub fn create_new_game_object_with_transform(
-        model: StarryModel,
-        transform: TransformComponent,
-    ) -> Self {
-        unsafe {
-            CURRENT_ID += 1;
-            StarryGameObject::new_with_transform(CURRENT_ID, model, transform)
+    // rounded so float noise does not show up in saved scenes
+    fn get_reflected_rotation(&self) -> Vector3<f32> {
+        self.get_euler_rotation()
+            .map(|angle| (angle * 1e4).round() / 1e4 + 0.0)
+    }
+
+    pub fn set_euler_rotation(&mut self, rotation: Vector3<f32>) {
+        self.rotation = euler_to_quaternion(rotation);
+    }
+
+    pub fn rotate_euler(&mut self, rotation: Vector3<f32>) {
+        self.rotation = (self.rotation * euler_to_quaternion(rotation)).normalize();
+    }
+
+    pub fn rotate_yaw_pitch(&mut self, yaw: f32, pitch: f32, max_pitch: f32) {
+        let current_pitch = self.get_euler_rotation().x;
+        let pitch = (current_pitch + pitch).clamp(-max_pitch, max_pitch) - current_pitch;
+
+        self.rotation = (Quaternion::from_angle_y(Deg(yaw))
+            * self.rotation
+            * Quaternion::from_angle_x(Deg(pitch)))
+        .normalize();
+    }
+
+    pub fn get_forward(&self) -> Vector3<f32> {
+        self.rotation.rotate_vector(Vector3::unit_z())
+    }
+
+    pub fn get_right(&self) -> Vector3<f32> {
+        self.rotation.rotate_vector(Vector3::unit_x())
+    }
+
+    pub fn get_up(&self) -> Vector3<f32> {
+        -self.rotation.rotate_vector(Vector3::unit_y())
+    }
+
+    pub fn look_at(&mut self, target: Vector3<f32>, up: Vector3<f32>) {
+        let forward = target - self.translation;
+        let right = (-up).cross(forward);
+
+        if forward.magnitude2() <= f32::EPSILON || right.magnitude2() <= f32::EPSILON {
+            return;
         }
+
+        let forward = forward.normalize();
+        let right = right.normalize();
+        let down = forward.cross(right);
+
+        self.rotation = Quaternion::from(Matrix3::from_cols(right, down, forward)).normalize();
     }
 
-    pub const fn get_id(&self) -> u32 {
-        self.id
+    pub fn rotate_around(&mut self, point: Vector3<f32>, rotation: Quaternion<f32>) {
+        self.translation = point + rotation.rotate_vector(self.translation - point);
+        self.rotation = (rotation * self.rotation).normalize();
     }
 
     pub fn move_in_plane_xz(
         &mut self,
         dt: f32,
-        key_pressed: KeyboardInput,
+        rotate: Vector3<f32>,
+        movement: Vector3<f32>,
         look_speed: f32,
         move_speed: f32,
     ) {
-
-        let mut rotate: Vector3<f32> = Vector3::zero();
-        let mut move_dir: Vector3<f32> = Vector3::zero();
-
-        let yaw = self.transform.rotation.y;
+        let forward = self.get_forward();
         let forward_dir = Vector3 {
-            x: Deg(yaw).sin(),
+            x: forward.x,
             y: 0.0,
-            z: Deg(yaw).cos()
+            z: forward.z
+        };
+
+        let forward_dir = if forward_dir.magnitude2() > f32::EPSILON {
+            forward_dir.normalize()
+        } else {
+            forward_dir
         };
 
         let right_dir = Vector3 {
@@ -104,61 +268,74 @@ impl StarryGameObject {
             z: 0.0
         };
 
-        match key_pressed.virtual_keycode {
-            Some(k) => {
-                match k {
-                    VirtualKeyCode::Left => {
-                        rotate.y -= 1.0;
-                    }
-
-                    VirtualKeyCode::Right => {
-                        rotate.y += 1.0;
-                    }
-
-                    VirtualKeyCode::Up => {
-                        rotate.x += 1.0;
-                    }
+        let move_dir = right_dir * movement.x + up_dir * movement.y + forward_dir * movement.z;
 
-                    VirtualKeyCode::Down => {
-                        rotate.x -= 1.0;
-                    }
-
-                    VirtualKeyCode::W => {
-                        move_dir += forward_dir;
-                    }
+        if rotate.dot(rotate) > f32::EPSILON {
+            let rotate = look_speed * dt * rotate.normalize();
+            self.rotation = (Quaternion::from_angle_y(Deg(rotate.y))
+                * self.rotation
+                * Quaternion::from_angle_x(Deg(rotate.x)))
+            .normalize();
+        }
+        if move_dir.dot(move_dir) > f32::EPSILON {
+            self.translation += move_speed * dt * move_dir.normalize(); 
+        }
+    }
+}
 
-                    VirtualKeyCode::S => {
-                        move_dir -= forward_dir;
-                    }
+pub fn euler_to_quaternion(rotation: Vector3<f32>) -> Quaternion<f32> {
+    Quaternion::from_angle_y(Deg(rotation.y))
+        * Quaternion::from_angle_x(Deg(rotation.x))
+        * Quaternion::from_angle_z(Deg(rotation.z))
+}
 
-                    VirtualKeyCode::D => {
-                        move_dir += right_dir;
-                    }
+pub struct StarryGameObject {
+    id: Entity,
+    pub model: StarryModel,
+    pub transform: TransformComponent,
+}
 
-                    VirtualKeyCode::A => {
-                        move_dir -= right_dir;
-                    }
+impl StarryGameObject {
+    fn new(id: Entity, model: StarryModel) -> Self {
+        Self {
+            id,
+            model,
+            transform: TransformComponent::default(),
+        }
+    }
 
-                    VirtualKeyCode::Q => {
-                        move_dir += up_dir;
-                    }
+    fn new_with_transform(id: Entity, model: StarryModel, transform: TransformComponent) -> Self {
+        Self {
+            id,
+            model,
+            transform,
+        }
+    }
 
-                    VirtualKeyCode::E => {
-                        move_dir -= up_dir;
-                    }
+    pub fn create_new_game_object(world: &mut World, model: StarryModel) -> Self {
+        StarryGameObject::new(world.spawn_empty(), model)
+    }
 
-                    _ => ()
-                }
-            }
+    pub fn create_new_game_object_with_transform(
+        world: &mut World,
+        model: StarryModel,
+        transform: TransformComponent,
+    ) -> Self {
+        StarryGameObject::new_with_transform(world.spawn_empty(), model, transform)
+    }
 
-            _ => ()
-        }
+    pub const fn get_id(&self) -> Entity {
+        self.id
+    }
 
-        if rotate.dot(rotate) > f32::EPSILON {
-            self.transform.rotation = self.transform.rotation % 360.0 + (look_speed * dt * rotate.normalize());
-        }
-        if move_dir.dot(move_dir) > f32::EPSILON {
-            self.transform.translation += move_speed * dt * move_dir.normalize(); 
-        }
+    pub fn move_in_plane_xz(
+        &mut self,
+        dt: f32,
+        rotate: Vector3<f32>,
+        movement: Vector3<f32>,
+        look_speed: f32,
+        move_speed: f32,
+    ) {
+        self.transform.move_in_plane_xz(dt, rotate, movement, look_speed, move_speed);
     }
 }
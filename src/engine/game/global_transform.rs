@@ -0,0 +1,146 @@
+use cgmath::{Matrix4, SquareMatrix, Vector3};
+
+use crate::{
+    engine::ecs::{
+        command::Commands,
+        component::Component,
+        entity::Entity,
+        hierarchy::{Children, Parent},
+        query::{Query, With, Without},
+        resource::Res,
+        world::World,
+    },
+    impl_reflect_struct,
+};
+
+use super::{
+    game_loop::{FixedTime, InterpolatedTransform},
+    game_object::TransformComponent,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTransform(Matrix4<f32>);
+
+impl Component for GlobalTransform {}
+
+impl_reflect_struct!(GlobalTransform { 0: Matrix4<f32> });
+
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        Self(Matrix4::identity())
+    }
+}
+
+impl GlobalTransform {
+    pub fn new(matrix: Matrix4<f32>) -> Self {
+        Self(matrix)
+    }
+
+    pub fn get_matrix(&self) -> Matrix4<f32> {
+        self.0
+    }
+
+    pub fn get_translation(&self) -> Vector3<f32> {
+        self.0.w.truncate()
+    }
+}
+
+pub fn add_global_transforms(
+    mut commands: Commands,
+    missing: Query<Entity, (With<TransformComponent>, Without<GlobalTransform>)>,
+) {
+    for entity in missing.iter() {
+        commands.entity(entity).insert(GlobalTransform::default());
+    }
+}
+
+type PropagateQuery<'w> = Query<
+    'w,
+    (
+        &'static TransformComponent,
+        Option<&'static InterpolatedTransform>,
+        Option<&'static Children>,
+        &'static mut GlobalTransform,
+    ),
+>;
+
+pub fn propagate_transforms(
+    roots: Query<Entity, (With<TransformComponent>, Without<Parent>)>,
+    mut transforms: PropagateQuery,
+    fixed_time: Option<Res<FixedTime>>,
+) {
+    let alpha = fixed_time.map_or(1.0, |fixed_time| fixed_time.get_alpha());
+
+    for root in roots.iter() {
+        propagate(root, Matrix4::identity(), alpha, &mut transforms);
+    }
+}
+
+fn propagate(
+    entity: Entity,
+    parent_matrix: Matrix4<f32>,
+    alpha: f32,
+    transforms: &mut PropagateQuery,
+) {
+    let (matrix, children) = match transforms.get_mut(entity) {
+        Some((transform, interpolated, children, mut global_transform)) => {
+            let local = match interpolated {
+                Some(interpolated) => interpolated.interpolate(transform, alpha),
+                None => *transform,
+            };
+
+            let matrix = parent_matrix * local.get_transform_matrix();
+            if global_transform.0 != matrix {
+                global_transform.0 = matrix;
+            }
+
+            (matrix, children.map(|children| children.get().to_vec()))
+        }
+
+        None => return,
+    };
+
+    for child in children.into_iter().flatten() {
+        propagate(child, matrix, alpha, transforms);
+    }
+}
+
+pub fn compute_world_matrix(world: &World, entity: Entity) -> Matrix4<f32> {
+    let local = world
+        .get::<TransformComponent>(entity)
+        .map(|transform| transform.get_transform_matrix())
+        .unwrap_or_else(Matrix4::identity);
+
+    match world.get::<Parent>(entity).map(|parent| parent.get()) {
+        Some(parent) => compute_world_matrix(world, parent) * local,
+        None => local,
+    }
+}
+
+pub fn set_parent_in_place(world: &mut World, child: Entity, parent: Entity) {
+    let child_matrix = compute_world_matrix(world, child);
+    let parent_matrix = compute_world_matrix(world, parent);
+    let parent_inverse = parent_matrix
+        .invert()
+        .expect("Cannot reparent onto a transform with zero scale.");
+
+    world.add_child(parent, child);
+    set_local_transform(world, child, TransformComponent::from_matrix(parent_inverse * child_matrix));
+}
+
+pub fn remove_parent_in_place(world: &mut World, child: Entity) {
+    let child_matrix = compute_world_matrix(world, child);
+
+    if world.remove_parent(child).is_some() {
+        set_local_transform(world, child, TransformComponent::from_matrix(child_matrix));
+    }
+}
+
+fn set_local_transform(world: &mut World, entity: Entity, transform: TransformComponent) {
+    match world.get_mut::<TransformComponent>(entity) {
+        Some(mut local) => *local = transform,
+        None => {
+            world.insert(entity, transform);
+        }
+    }
+}
@@ -1 +1,4 @@
-pub mod game_object;
\ No newline at end of file
+pub mod game_object;
+pub mod global_transform;
+pub mod game_loop;
+pub mod camera_controller;
\ No newline at end of file
@@ -0,0 +1,348 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use crate::engine::ecs::resource::Resource;
+
+use super::{
+    button::ButtonInput,
+    error::InputFileError,
+    gamepad::{GamepadAxis, GamepadButton, Gamepads},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputBinding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButton),
+    GamepadAxis { axis: GamepadAxis, positive: bool },
+}
+
+impl InputBinding {
+    fn get_value(
+        &self,
+        keys: &ButtonInput<VirtualKeyCode>,
+        mouse_buttons: &ButtonInput<MouseButton>,
+        gamepads: &Gamepads,
+    ) -> f32 {
+        // a press released within the same frame still counts for that frame
+        let pressed = match self {
+            Self::Key(key) => keys.is_pressed(*key) || keys.is_just_pressed(*key),
+            Self::Mouse(button) => {
+                mouse_buttons.is_pressed(*button) || mouse_buttons.is_just_pressed(*button)
+            }
+            Self::GamepadButton(button) => {
+                gamepads.is_any_pressed(*button) || gamepads.is_any_just_pressed(*button)
+            }
+            Self::GamepadAxis { axis, positive } => {
+                return gamepads.get_axis_strength(*axis, *positive)
+            }
+        };
+
+        if pressed {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxisBinding {
+    #[serde(default)]
+    pub positive: Vec<InputBinding>,
+    #[serde(default)]
+    pub negative: Vec<InputBinding>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMap {
+    #[serde(default)]
+    actions: BTreeMap<String, Vec<InputBinding>>,
+    #[serde(default)]
+    axes: BTreeMap<String, AxisBinding>,
+    #[serde(skip)]
+    pending_rebind: Option<String>,
+}
+
+impl Resource for InputMap {}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, InputFileError> {
+        let json = fs::read_to_string(path).map_err(|error| InputFileError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        Self::from_json(&json).map_err(|error| InputFileError::Parse {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    // a missing file is the normal first run, a broken one falls back to the defaults too
+    pub fn load_or_default(path: &str) -> Self {
+        if !Path::new(path).exists() {
+            return Self::default();
+        }
+
+        Self::load_from_file(path).unwrap_or_else(|error| {
+            println!("{error}");
+            Self::default()
+        })
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), InputFileError> {
+        fs::write(path, self.to_json()).map_err(|error| InputFileError::Io {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn bind_action(&mut self, action: &str, binding: InputBinding) -> &mut Self {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+
+        self
+    }
+
+    pub fn set_action_bindings(&mut self, action: &str, bindings: Vec<InputBinding>) -> &mut Self {
+        self.actions.insert(action.to_string(), bindings);
+        self
+    }
+
+    pub fn rebind_action(&mut self, action: &str, old: InputBinding, new: InputBinding) -> bool {
+        match self
+            .actions
+            .get_mut(action)
+            .and_then(|bindings| bindings.iter_mut().find(|binding| **binding == old))
+        {
+            Some(binding) => {
+                *binding = new;
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    pub fn unbind_action(&mut self, action: &str, binding: InputBinding) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    pub fn get_action_bindings(&self, action: &str) -> &[InputBinding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get_actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+
+    pub fn bind_axis(
+        &mut self,
+        axis: &str,
+        positive: InputBinding,
+        negative: InputBinding,
+    ) -> &mut Self {
+        let binding = self.axes.entry(axis.to_string()).or_default();
+        binding.positive.push(positive);
+        binding.negative.push(negative);
+        self
+    }
+
+    pub fn set_axis_binding(&mut self, axis: &str, binding: AxisBinding) -> &mut Self {
+        self.axes.insert(axis.to_string(), binding);
+        self
+    }
+
+    pub fn get_axis_binding(&self, axis: &str) -> Option<&AxisBinding> {
+        self.axes.get(axis)
+    }
+
+    pub fn get_axes(&self) -> impl Iterator<Item = &str> {
+        self.axes.keys().map(String::as_str)
+    }
+
+    pub fn request_rebind(&mut self, action: &str) {
+        self.pending_rebind = Some(action.to_string());
+    }
+
+    pub fn cancel_rebind(&mut self) {
+        self.pending_rebind = None;
+    }
+
+    pub fn get_pending_rebind(&self) -> Option<&str> {
+        self.pending_rebind.as_deref()
+    }
+
+    pub(crate) fn apply_pending_rebind(
+        &mut self,
+        keys: &ButtonInput<VirtualKeyCode>,
+        mouse_buttons: &ButtonInput<MouseButton>,
+        gamepads: &Gamepads,
+    ) -> Option<(String, InputBinding)> {
+        self.pending_rebind.as_ref()?;
+
+        let binding = keys
+            .get_just_pressed()
+            .map(|key| InputBinding::Key(*key))
+            .chain(
+                mouse_buttons
+                    .get_just_pressed()
+                    .map(|button| InputBinding::Mouse(*button)),
+            )
+            .chain(gamepads.get_just_pressed().map(InputBinding::GamepadButton))
+            .next()?;
+
+        let action = self.pending_rebind.take().unwrap();
+        self.set_action_bindings(&action, vec![binding]);
+        Some((action, binding))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ActionData {
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ActionState {
+    actions: HashMap<String, ActionData>,
+    axes: HashMap<String, f32>,
+}
+
+impl Resource for ActionState {}
+
+impl ActionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(
+        &mut self,
+        input_map: &InputMap,
+        keys: &ButtonInput<VirtualKeyCode>,
+        mouse_buttons: &ButtonInput<MouseButton>,
+        gamepads: &Gamepads,
+    ) {
+        let threshold = gamepads.get_settings().press_threshold;
+        let get_value = |bindings: &[InputBinding]| {
+            bindings
+                .iter()
+                .map(|binding| binding.get_value(keys, mouse_buttons, gamepads))
+                .fold(0.0, f32::max)
+        };
+
+        self.actions
+            .retain(|action, _| input_map.actions.contains_key(action));
+
+        for (action, bindings) in &input_map.actions {
+            let pressed = get_value(bindings) >= threshold;
+
+            let data = self.actions.entry(action.clone()).or_default();
+            data.just_pressed = pressed && !data.pressed;
+            data.just_released = !pressed && data.pressed;
+            data.pressed = pressed;
+        }
+
+        self.axes = input_map
+            .axes
+            .iter()
+            .map(|(axis, binding)| {
+                (
+                    axis.clone(),
+                    get_value(&binding.positive) - get_value(&binding.negative),
+                )
+            })
+            .collect();
+    }
+
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.actions.get(action).is_some_and(|data| data.pressed)
+    }
+
+    pub fn is_just_pressed(&self, action: &str) -> bool {
+        self.actions
+            .get(action)
+            .is_some_and(|data| data.just_pressed)
+    }
+
+    pub fn is_just_released(&self, action: &str) -> bool {
+        self.actions
+            .get(action)
+            .is_some_and(|data| data.just_released)
+    }
+
+    pub fn get_axis(&self, axis: &str) -> f32 {
+        self.axes.get(axis).copied().unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_and_release_in_one_frame_is_just_pressed() {
+        let mut input_map = InputMap::new();
+        input_map.bind_action("jump", InputBinding::Key(VirtualKeyCode::Space));
+
+        let mut keys = ButtonInput::new();
+        let mouse_buttons = ButtonInput::new();
+        let gamepads = Gamepads::default();
+        let mut action_state = ActionState::new();
+
+        keys.press(VirtualKeyCode::Space);
+        keys.release(VirtualKeyCode::Space);
+        action_state.update(&input_map, &keys, &mouse_buttons, &gamepads);
+        assert!(action_state.is_just_pressed("jump"));
+
+        keys.clear();
+        action_state.update(&input_map, &keys, &mouse_buttons, &gamepads);
+        assert!(!action_state.is_pressed("jump"));
+        assert!(action_state.is_just_released("jump"));
+    }
+
+    #[test]
+    fn unreadable_bindings_fall_back_to_defaults() {
+        let path = std::env::temp_dir().join("starry_broken_bindings.json");
+        let path = path.to_str().unwrap();
+        fs::write(path, "{ \"actions\": [").unwrap();
+
+        assert!(matches!(
+            InputMap::load_from_file(path),
+            Err(InputFileError::Parse { .. })
+        ));
+        assert_eq!(InputMap::load_or_default(path), InputMap::default());
+        assert_eq!(
+            InputMap::load_or_default("assets/input/missing.json"),
+            InputMap::default()
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+use std::{collections::HashSet, hash::Hash};
+
+use crate::engine::ecs::resource::Resource;
+
+pub struct ButtonInput<T: Copy + Eq + Hash + Send + Sync + 'static> {
+    pressed: HashSet<T>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> Resource for ButtonInput<T> {}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> Default for ButtonInput<T> {
+    fn default() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> ButtonInput<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn press(&mut self, button: T) {
+        if self.pressed.insert(button) {
+            self.just_pressed.insert(button);
+        }
+    }
+
+    pub fn release(&mut self, button: T) {
+        if self.pressed.remove(&button) {
+            self.just_released.insert(button);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.just_released.extend(self.pressed.drain());
+    }
+
+    pub fn is_pressed(&self, button: T) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    pub fn is_just_pressed(&self, button: T) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    pub fn is_just_released(&self, button: T) -> bool {
+        self.just_released.contains(&button)
+    }
+
+    pub fn is_any_pressed(&self, buttons: impl IntoIterator<Item = T>) -> bool {
+        buttons.into_iter().any(|button| self.is_pressed(button))
+    }
+
+    pub fn get_pressed(&self) -> impl Iterator<Item = &T> {
+        self.pressed.iter()
+    }
+
+    pub fn get_just_pressed(&self) -> impl Iterator<Item = &T> {
+        self.just_pressed.iter()
+    }
+
+    pub fn get_just_released(&self) -> impl Iterator<Item = &T> {
+        self.just_released.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    pub fn reset_all(&mut self) {
+        self.pressed.clear();
+        self.clear();
+    }
+}
@@ -0,0 +1,31 @@
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+pub enum InputFileError {
+    Io {
+        path: String,
+        error: io::Error,
+    },
+    Parse {
+        path: String,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for InputFileError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(formatter, "Failed to access {path}: {error}"),
+            Self::Parse { path, error } => write!(formatter, "Failed to parse {path}: {error}"),
+        }
+    }
+}
+
+impl Error for InputFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Parse { error, .. } => Some(error),
+        }
+    }
+}
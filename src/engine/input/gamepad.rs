@@ -0,0 +1,581 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use cgmath::{InnerSpace, Vector2, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::ecs::{
+    event::{Event, EventReader, EventWriter},
+    resource::{ResMut, Resource},
+};
+
+use super::button::ButtonInput;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GamepadId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub fn is_trigger(&self) -> bool {
+        matches!(self, Self::LeftTrigger | Self::RightTrigger)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadStick {
+    Left,
+    Right,
+}
+
+impl GamepadStick {
+    pub fn get_axes(&self) -> (GamepadAxis, GamepadAxis) {
+        match self {
+            Self::Left => (GamepadAxis::LeftStickX, GamepadAxis::LeftStickY),
+            Self::Right => (GamepadAxis::RightStickX, GamepadAxis::RightStickY),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadEventKind {
+    Connected,
+    Disconnected,
+    ButtonChanged {
+        button: GamepadButton,
+        pressed: bool,
+    },
+    AxisChanged {
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GamepadEvent {
+    pub gamepad: GamepadId,
+    pub kind: GamepadEventKind,
+}
+
+impl Event for GamepadEvent {}
+
+impl GamepadEvent {
+    pub fn new(gamepad: GamepadId, kind: GamepadEventKind) -> Self {
+        Self { gamepad, kind }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseCurve {
+    Linear,
+    Quadratic,
+    Cubic,
+    Power(f32),
+}
+
+impl ResponseCurve {
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs().clamp(0.0, 1.0);
+
+        let curved = match self {
+            Self::Linear => magnitude,
+            Self::Quadratic => magnitude * magnitude,
+            Self::Cubic => magnitude * magnitude * magnitude,
+            Self::Power(exponent) => magnitude.powf(*exponent),
+        };
+
+        curved.copysign(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GamepadSettings {
+    pub stick_dead_zone: f32,
+    pub stick_outer_zone: f32,
+    pub trigger_dead_zone: f32,
+    pub stick_curve: ResponseCurve,
+    pub trigger_curve: ResponseCurve,
+    pub press_threshold: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            stick_dead_zone: 0.15,
+            stick_outer_zone: 0.95,
+            trigger_dead_zone: 0.05,
+            stick_curve: ResponseCurve::Linear,
+            trigger_curve: ResponseCurve::Linear,
+            press_threshold: 0.5,
+        }
+    }
+}
+
+impl GamepadSettings {
+    // rescales the magnitude between the dead and outer zone to 0..1 before applying the curve
+    fn process(
+        &self,
+        magnitude: f32,
+        dead_zone: f32,
+        outer_zone: f32,
+        curve: ResponseCurve,
+    ) -> f32 {
+        if magnitude <= dead_zone {
+            return 0.0;
+        }
+
+        let scaled = ((magnitude - dead_zone) / (outer_zone - dead_zone)).clamp(0.0, 1.0);
+        curve.apply(scaled)
+    }
+
+    pub fn process_stick(&self, raw: Vector2<f32>) -> Vector2<f32> {
+        let magnitude = raw.magnitude();
+        let processed = self.process(
+            magnitude,
+            self.stick_dead_zone,
+            self.stick_outer_zone,
+            self.stick_curve,
+        );
+
+        if processed == 0.0 {
+            Vector2::zero()
+        } else {
+            raw / magnitude * processed
+        }
+    }
+
+    pub fn process_trigger(&self, raw: f32) -> f32 {
+        self.process(raw, self.trigger_dead_zone, 1.0, self.trigger_curve)
+    }
+}
+
+#[derive(Default)]
+pub struct GamepadState {
+    buttons: ButtonInput<GamepadButton>,
+    axes: HashMap<GamepadAxis, f32>,
+}
+
+impl GamepadState {
+    pub fn get_buttons(&self) -> &ButtonInput<GamepadButton> {
+        &self.buttons
+    }
+
+    pub fn get_raw_axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Default)]
+pub struct Gamepads {
+    gamepads: BTreeMap<GamepadId, GamepadState>,
+    settings: GamepadSettings,
+}
+
+impl Resource for Gamepads {}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_settings(&self) -> &GamepadSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: GamepadSettings) {
+        self.settings = settings;
+    }
+
+    pub fn handle_event(&mut self, event: &GamepadEvent) {
+        match event.kind {
+            GamepadEventKind::Connected => {
+                self.gamepads.entry(event.gamepad).or_default();
+            }
+
+            GamepadEventKind::Disconnected => {
+                self.gamepads.remove(&event.gamepad);
+            }
+
+            GamepadEventKind::ButtonChanged { button, pressed } => {
+                let buttons = &mut self.gamepads.entry(event.gamepad).or_default().buttons;
+
+                if pressed {
+                    buttons.press(button);
+                } else {
+                    buttons.release(button);
+                }
+            }
+
+            GamepadEventKind::AxisChanged { axis, value } => {
+                self.gamepads
+                    .entry(event.gamepad)
+                    .or_default()
+                    .axes
+                    .insert(axis, value.clamp(-1.0, 1.0));
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.buttons.clear();
+        }
+    }
+
+    pub fn is_connected(&self, gamepad: GamepadId) -> bool {
+        self.gamepads.contains_key(&gamepad)
+    }
+
+    pub fn get_connected(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.gamepads.keys().copied()
+    }
+
+    pub fn get_gamepad(&self, gamepad: GamepadId) -> Option<&GamepadState> {
+        self.gamepads.get(&gamepad)
+    }
+
+    pub fn is_pressed(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&gamepad)
+            .is_some_and(|state| state.buttons.is_pressed(button))
+    }
+
+    pub fn is_just_pressed(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&gamepad)
+            .is_some_and(|state| state.buttons.is_just_pressed(button))
+    }
+
+    pub fn is_any_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepads
+            .values()
+            .any(|state| state.buttons.is_pressed(button))
+    }
+
+    pub fn is_any_just_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepads
+            .values()
+            .any(|state| state.buttons.is_just_pressed(button))
+    }
+
+    pub fn get_just_pressed(&self) -> impl Iterator<Item = GamepadButton> + '_ {
+        self.gamepads
+            .values()
+            .flat_map(|state| state.buttons.get_just_pressed().copied())
+    }
+
+    pub fn get_stick(&self, gamepad: GamepadId, stick: GamepadStick) -> Vector2<f32> {
+        let state = match self.gamepads.get(&gamepad) {
+            Some(state) => state,
+            None => return Vector2::zero(),
+        };
+
+        let (x, y) = stick.get_axes();
+        self.settings
+            .process_stick(Vector2::new(state.get_raw_axis(x), state.get_raw_axis(y)))
+    }
+
+    pub fn get_axis(&self, gamepad: GamepadId, axis: GamepadAxis) -> f32 {
+        match axis {
+            GamepadAxis::LeftStickX => self.get_stick(gamepad, GamepadStick::Left).x,
+            GamepadAxis::LeftStickY => self.get_stick(gamepad, GamepadStick::Left).y,
+            GamepadAxis::RightStickX => self.get_stick(gamepad, GamepadStick::Right).x,
+            GamepadAxis::RightStickY => self.get_stick(gamepad, GamepadStick::Right).y,
+            GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => {
+                self.gamepads.get(&gamepad).map_or(0.0, |state| {
+                    self.settings.process_trigger(state.get_raw_axis(axis))
+                })
+            }
+        }
+    }
+
+    // strongest deflection in one direction across all connected gamepads, always positive
+    pub fn get_axis_strength(&self, axis: GamepadAxis, positive: bool) -> f32 {
+        self.get_connected()
+            .map(|gamepad| {
+                let value = self.get_axis(gamepad, axis);
+                if positive {
+                    value.max(0.0)
+                } else {
+                    (-value).max(0.0)
+                }
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+pub trait GamepadBackend: Send + Sync {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>);
+}
+
+pub struct StarryGamepadBackend {
+    backend: Box<dyn GamepadBackend>,
+}
+
+impl Resource for StarryGamepadBackend {}
+
+impl StarryGamepadBackend {
+    pub fn new(backend: impl GamepadBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        let mut events = Vec::new();
+        self.backend.poll(&mut events);
+        events
+    }
+}
+
+type GamepadEventQueue = Arc<Mutex<VecDeque<GamepadEvent>>>;
+
+#[derive(Debug, Clone)]
+pub struct VirtualGamepad {
+    id: GamepadId,
+    queue: GamepadEventQueue,
+}
+
+impl VirtualGamepad {
+    pub fn get_id(&self) -> GamepadId {
+        self.id
+    }
+
+    fn send(&self, kind: GamepadEventKind) {
+        self.queue
+            .lock()
+            .unwrap()
+            .push_back(GamepadEvent::new(self.id, kind));
+    }
+
+    pub fn connect(&self) {
+        self.send(GamepadEventKind::Connected);
+    }
+
+    pub fn disconnect(&self) {
+        self.send(GamepadEventKind::Disconnected);
+    }
+
+    pub fn press(&self, button: GamepadButton) {
+        self.send(GamepadEventKind::ButtonChanged {
+            button,
+            pressed: true,
+        });
+    }
+
+    pub fn release(&self, button: GamepadButton) {
+        self.send(GamepadEventKind::ButtonChanged {
+            button,
+            pressed: false,
+        });
+    }
+
+    pub fn set_axis(&self, axis: GamepadAxis, value: f32) {
+        self.send(GamepadEventKind::AxisChanged { axis, value });
+    }
+
+    pub fn set_stick(&self, stick: GamepadStick, value: Vector2<f32>) {
+        let (x, y) = stick.get_axes();
+        self.set_axis(x, value.x);
+        self.set_axis(y, value.y);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VirtualGamepadBackend {
+    queue: GamepadEventQueue,
+    script: Vec<(u64, GamepadEvent)>,
+    frame: u64,
+}
+
+impl VirtualGamepadBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create_gamepad(&self, id: GamepadId) -> VirtualGamepad {
+        VirtualGamepad {
+            id,
+            queue: self.queue.clone(),
+        }
+    }
+
+    pub fn schedule(&mut self, frame: u64, event: GamepadEvent) -> &mut Self {
+        let index = self
+            .script
+            .partition_point(|(scheduled, _)| *scheduled <= frame);
+        self.script.insert(index, (frame, event));
+        self
+    }
+
+    pub fn get_frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+impl GamepadBackend for VirtualGamepadBackend {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        let due = self
+            .script
+            .partition_point(|(scheduled, _)| *scheduled <= self.frame);
+
+        events.extend(self.script.drain(..due).map(|(_, event)| event));
+        events.extend(self.queue.lock().unwrap().drain(..));
+        self.frame += 1;
+    }
+}
+
+pub fn poll_gamepad_backend(
+    backend: Option<ResMut<StarryGamepadBackend>>,
+    mut events: EventWriter<GamepadEvent>,
+) {
+    if let Some(mut backend) = backend {
+        for event in backend.poll() {
+            events.send(event);
+        }
+    }
+}
+
+pub fn update_gamepads(mut gamepads: ResMut<Gamepads>, mut events: EventReader<GamepadEvent>) {
+    gamepads.clear();
+
+    for event in events.read() {
+        gamepads.handle_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{App, CorePlugin},
+        engine::input::{
+            action::{ActionState, InputBinding, InputMap},
+            plugin::InputPlugin,
+        },
+    };
+
+    const PAD: GamepadId = GamepadId(0);
+
+    fn axis(axis: GamepadAxis, positive: bool) -> InputBinding {
+        InputBinding::GamepadAxis { axis, positive }
+    }
+
+    #[test]
+    fn virtual_gamepad_drives_actions() {
+        let settings = GamepadSettings {
+            stick_curve: ResponseCurve::Quadratic,
+            ..GamepadSettings::default()
+        };
+
+        let mut backend = VirtualGamepadBackend::new();
+        backend
+            .schedule(0, GamepadEvent::new(PAD, GamepadEventKind::Connected))
+            .schedule(
+                0,
+                GamepadEvent::new(
+                    PAD,
+                    GamepadEventKind::AxisChanged {
+                        axis: GamepadAxis::LeftStickY,
+                        value: 0.55,
+                    },
+                ),
+            )
+            .schedule(
+                0,
+                GamepadEvent::new(
+                    PAD,
+                    GamepadEventKind::AxisChanged {
+                        axis: GamepadAxis::RightStickX,
+                        value: 0.1,
+                    },
+                ),
+            )
+            .schedule(
+                1,
+                GamepadEvent::new(
+                    PAD,
+                    GamepadEventKind::ButtonChanged {
+                        button: GamepadButton::South,
+                        pressed: true,
+                    },
+                ),
+            );
+
+        let mut input_map = InputMap::new();
+        input_map
+            .bind_axis(
+                "move_forward",
+                axis(GamepadAxis::LeftStickY, true),
+                axis(GamepadAxis::LeftStickY, false),
+            )
+            .bind_axis(
+                "look_yaw",
+                axis(GamepadAxis::RightStickX, true),
+                axis(GamepadAxis::RightStickX, false),
+            )
+            .bind_action("jump", InputBinding::GamepadButton(GamepadButton::South));
+
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(InputPlugin::default())
+            .insert_resource(input_map)
+            .insert_resource(StarryGamepadBackend::new(backend));
+        app.get_world_mut()
+            .resource_mut::<Gamepads>()
+            .set_settings(settings);
+
+        app.update();
+        {
+            let world = app.get_world();
+            let gamepads = world.resource::<Gamepads>();
+            let actions = world.resource::<ActionState>();
+
+            // 0.55 sits halfway between the 0.15 dead zone and the 0.95 outer zone, squared
+            let stick = settings.process_stick(Vector2::new(0.0, 0.55));
+            assert!((stick.y - 0.25).abs() < 1e-5);
+            assert_eq!(gamepads.get_stick(PAD, GamepadStick::Left), stick);
+            assert_eq!(actions.get_axis("move_forward"), stick.y);
+
+            assert_eq!(gamepads.get_axis(PAD, GamepadAxis::RightStickX), 0.0);
+            assert_eq!(actions.get_axis("look_yaw"), 0.0);
+            assert!(!actions.is_pressed("jump"));
+        }
+
+        app.update();
+        let actions = app.get_world().resource::<ActionState>();
+        assert!(actions.is_just_pressed("jump"));
+        assert!((actions.get_axis("move_forward") - 0.25).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use crate::app::{App, Plugin};
+
+use super::gamepad::{
+    GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, GamepadEventKind, GamepadId,
+    StarryGamepadBackend,
+};
+
+// gilrs is not Send on every platform, so it lives on its own thread and forwards events
+pub struct GilrsBackend {
+    receiver: Mutex<Receiver<GamepadEvent>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Default for GilrsBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GilrsBackend {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+
+        thread::Builder::new()
+            .name("gilrs".to_string())
+            .spawn(move || poll_gilrs(sender, thread_running))
+            .expect("Failed to spawn gamepad thread.");
+
+        Self {
+            receiver: Mutex::new(receiver),
+            running,
+        }
+    }
+}
+
+// a failed send only notices the hang up once an event arrives, so the thread also polls this flag
+impl Drop for GilrsBackend {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl GamepadBackend for GilrsBackend {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        events.extend(self.receiver.get_mut().unwrap().try_iter());
+    }
+}
+
+pub struct GilrsPlugin;
+
+impl Plugin for GilrsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StarryGamepadBackend::new(GilrsBackend::new()));
+    }
+}
+
+fn poll_gilrs(sender: Sender<GamepadEvent>, running: Arc<AtomicBool>) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(error) => {
+            println!("Failed to initialize gamepads: {error}");
+            return;
+        }
+    };
+
+    for (id, _) in gilrs.gamepads() {
+        let event = GamepadEvent::new(GamepadId(id.into()), GamepadEventKind::Connected);
+        if sender.send(event).is_err() {
+            return;
+        }
+    }
+
+    while running.load(Ordering::Relaxed) {
+        while let Some(event) = gilrs.next_event() {
+            let kind = match convert_event(event.event) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            if sender
+                .send(GamepadEvent::new(GamepadId(event.id.into()), kind))
+                .is_err()
+            {
+                return;
+            }
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn convert_event(event: EventType) -> Option<GamepadEventKind> {
+    match event {
+        EventType::Connected => Some(GamepadEventKind::Connected),
+        EventType::Disconnected => Some(GamepadEventKind::Disconnected),
+
+        EventType::ButtonPressed(button, _) => {
+            convert_button(button).map(|button| GamepadEventKind::ButtonChanged {
+                button,
+                pressed: true,
+            })
+        }
+
+        EventType::ButtonReleased(button, _) => {
+            convert_button(button).map(|button| GamepadEventKind::ButtonChanged {
+                button,
+                pressed: false,
+            })
+        }
+
+        EventType::ButtonChanged(Button::LeftTrigger2, value, _) => {
+            Some(GamepadEventKind::AxisChanged {
+                axis: GamepadAxis::LeftTrigger,
+                value,
+            })
+        }
+
+        EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+            Some(GamepadEventKind::AxisChanged {
+                axis: GamepadAxis::RightTrigger,
+                value,
+            })
+        }
+
+        EventType::AxisChanged(axis, value, _) => {
+            convert_axis(axis).map(|axis| GamepadEventKind::AxisChanged { axis, value })
+        }
+
+        _ => None,
+    }
+}
+
+fn convert_button(button: Button) -> Option<GamepadButton> {
+    match button {
+        Button::South => Some(GamepadButton::South),
+        Button::East => Some(GamepadButton::East),
+        Button::North => Some(GamepadButton::North),
+        Button::West => Some(GamepadButton::West),
+        Button::LeftTrigger => Some(GamepadButton::LeftBumper),
+        Button::RightTrigger => Some(GamepadButton::RightBumper),
+        Button::Select => Some(GamepadButton::Select),
+        Button::Start => Some(GamepadButton::Start),
+        Button::Mode => Some(GamepadButton::Mode),
+        Button::LeftThumb => Some(GamepadButton::LeftThumb),
+        Button::RightThumb => Some(GamepadButton::RightThumb),
+        Button::DPadUp => Some(GamepadButton::DPadUp),
+        Button::DPadDown => Some(GamepadButton::DPadDown),
+        Button::DPadLeft => Some(GamepadButton::DPadLeft),
+        Button::DPadRight => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
+
+fn convert_axis(axis: Axis) -> Option<GamepadAxis> {
+    match axis {
+        Axis::LeftStickX => Some(GamepadAxis::LeftStickX),
+        Axis::LeftStickY => Some(GamepadAxis::LeftStickY),
+        Axis::RightStickX => Some(GamepadAxis::RightStickX),
+        Axis::RightStickY => Some(GamepadAxis::RightStickY),
+        _ => None,
+    }
+}
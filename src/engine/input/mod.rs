@@ -0,0 +1,9 @@
+pub mod button;
+pub mod gamepad;
+#[cfg(feature = "gilrs")]
+pub mod gilrs_backend;
+pub mod action;
+pub mod error;
+pub mod mouse;
+pub mod plugin;
+pub mod recording;
\ No newline at end of file
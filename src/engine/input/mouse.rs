@@ -0,0 +1,146 @@
+use cgmath::{Vector2, Zero};
+
+use crate::engine::{
+    ecs::{
+        event::EventReader,
+        resource::{Res, ResMut, Resource},
+    },
+    window::{MouseMotionEvent, MouseWheelEvent, StarryWindow},
+};
+
+use super::action::ActionState;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MouseMotion {
+    delta: Vector2<f32>,
+    scroll: f32,
+}
+
+impl Default for MouseMotion {
+    fn default() -> Self {
+        Self {
+            delta: Vector2::zero(),
+            scroll: 0.0,
+        }
+    }
+}
+
+impl Resource for MouseMotion {}
+
+impl MouseMotion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn accumulate(&mut self, delta: Vector2<f32>) {
+        self.delta += delta;
+    }
+
+    pub fn accumulate_scroll(&mut self, lines: f32) {
+        self.scroll += lines;
+    }
+
+    pub fn clear(&mut self) {
+        self.delta = Vector2::zero();
+        self.scroll = 0.0;
+    }
+
+    pub fn get_delta(&self) -> Vector2<f32> {
+        self.delta
+    }
+
+    pub fn get_scroll(&self) -> f32 {
+        self.scroll
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MouseLook {
+    pub sensitivity: f32,
+    pub invert_y: bool,
+    pub max_pitch: f32,
+    cursor_grabbed: bool,
+}
+
+impl Default for MouseLook {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.1,
+            invert_y: false,
+            max_pitch: 89.0,
+            cursor_grabbed: false,
+        }
+    }
+}
+
+impl Resource for MouseLook {}
+
+impl MouseLook {
+    pub fn new(sensitivity: f32, invert_y: bool) -> Self {
+        Self {
+            sensitivity,
+            invert_y,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.cursor_grabbed
+    }
+
+    pub fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.cursor_grabbed = grabbed;
+    }
+
+    // x is yaw and y is pitch in degrees, pixels moved down look down unless inverted
+    pub fn get_look_delta(&self, motion: &MouseMotion) -> Vector2<f32> {
+        if !self.cursor_grabbed {
+            return Vector2::zero();
+        }
+
+        let delta = motion.get_delta() * self.sensitivity;
+        let pitch = if self.invert_y { delta.y } else { -delta.y };
+
+        Vector2::new(delta.x, pitch)
+    }
+}
+
+pub fn update_mouse_motion(
+    mut motion: ResMut<MouseMotion>,
+    mut events: EventReader<MouseMotionEvent>,
+    mut wheel_events: EventReader<MouseWheelEvent>,
+) {
+    motion.clear();
+
+    for event in events.read() {
+        motion.accumulate(Vector2::new(event.delta.0 as f32, event.delta.1 as f32));
+    }
+
+    for event in wheel_events.read() {
+        motion.accumulate_scroll(event.lines);
+    }
+}
+
+pub fn update_cursor_grab(
+    mut mouse_look: ResMut<MouseLook>,
+    actions: Res<ActionState>,
+    window: Option<Res<StarryWindow>>,
+) {
+    let grabbed = if actions.is_just_pressed("release_cursor") {
+        false
+    } else if actions.is_just_pressed("grab_cursor") {
+        true
+    } else {
+        return;
+    };
+
+    if grabbed == mouse_look.is_cursor_grabbed() {
+        return;
+    }
+
+    mouse_look.set_cursor_grabbed(grabbed);
+
+    if let Some(window) = window {
+        window.set_cursor_grab(grabbed);
+    }
+}
@@ -0,0 +1,153 @@
+use winit::event::{ElementState, MouseButton, VirtualKeyCode};
+
+use crate::{
+    app::{App, Plugin},
+    engine::{
+        ecs::{
+            event::{Event, EventReader, EventWriter},
+            resource::{Res, ResMut},
+            system::{IntoSystemDescriptor, Stage},
+        },
+        window::{
+            KeyboardInputEvent, MouseButtonInputEvent, MouseMotionEvent, MouseWheelEvent,
+            WindowFocusLost,
+        },
+    },
+};
+
+use super::{
+    action::{ActionState, InputBinding, InputMap},
+    button::ButtonInput,
+    gamepad::{poll_gamepad_backend, update_gamepads, GamepadEvent, Gamepads},
+    mouse::{update_cursor_grab, update_mouse_motion, MouseLook, MouseMotion},
+};
+
+#[derive(Debug, Clone)]
+pub struct ActionRebound {
+    pub action: String,
+    pub binding: InputBinding,
+}
+
+impl Event for ActionRebound {}
+
+pub struct InputPlugin {
+    pub bindings_path: String,
+}
+
+impl Default for InputPlugin {
+    fn default() -> Self {
+        Self {
+            bindings_path: "assets/input/bindings.json".to_string(),
+        }
+    }
+}
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KeyboardInputEvent>()
+            .add_event::<MouseButtonInputEvent>()
+            .add_event::<MouseMotionEvent>()
+            .add_event::<MouseWheelEvent>()
+            .add_event::<GamepadEvent>()
+            .add_event::<ActionRebound>()
+            .add_event::<WindowFocusLost>()
+            .init_resource::<ButtonInput<VirtualKeyCode>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<MouseMotion>()
+            .init_resource::<MouseLook>()
+            .init_resource::<ActionState>()
+            .insert_resource(InputMap::load_or_default(&self.bindings_path))
+            .add_system(
+                Stage::PreUpdate,
+                update_keyboard_input.label("keyboard_input"),
+            )
+            .add_system(
+                Stage::PreUpdate,
+                update_mouse_button_input.label("mouse_button_input"),
+            )
+            .add_system(
+                Stage::PreUpdate,
+                poll_gamepad_backend.label("gamepad_backend"),
+            )
+            .add_system(
+                Stage::PreUpdate,
+                update_gamepads
+                    .label("gamepad_input")
+                    .after("gamepad_backend"),
+            )
+            .add_system(
+                Stage::PreUpdate,
+                update_action_state
+                    .label("action_state")
+                    .after("keyboard_input")
+                    .after("mouse_button_input")
+                    .after("gamepad_input"),
+            )
+            .add_system(Stage::PreUpdate, update_mouse_motion.label("mouse_motion"))
+            .add_system(
+                Stage::PreUpdate,
+                update_cursor_grab
+                    .label("cursor_grab")
+                    .after("action_state"),
+            );
+    }
+}
+
+pub fn update_keyboard_input(
+    mut keys: ResMut<ButtonInput<VirtualKeyCode>>,
+    mut events: EventReader<KeyboardInputEvent>,
+    mut focus_lost: EventReader<WindowFocusLost>,
+) {
+    keys.clear();
+
+    for event in events.read() {
+        if let Some(key) = event.input.virtual_keycode {
+            match event.input.state {
+                ElementState::Pressed => keys.press(key),
+                ElementState::Released => keys.release(key),
+            }
+        }
+    }
+
+    // the window never sees the release of keys let go while unfocused
+    if focus_lost.read().next().is_some() {
+        keys.release_all();
+    }
+}
+
+pub fn update_mouse_button_input(
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut events: EventReader<MouseButtonInputEvent>,
+    mut focus_lost: EventReader<WindowFocusLost>,
+) {
+    mouse_buttons.clear();
+
+    for event in events.read() {
+        match event.state {
+            ElementState::Pressed => mouse_buttons.press(event.button),
+            ElementState::Released => mouse_buttons.release(event.button),
+        }
+    }
+
+    if focus_lost.read().next().is_some() {
+        mouse_buttons.release_all();
+    }
+}
+
+pub fn update_action_state(
+    mut action_state: ResMut<ActionState>,
+    mut input_map: ResMut<InputMap>,
+    mut rebound: EventWriter<ActionRebound>,
+    keys: Res<ButtonInput<VirtualKeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+) {
+    if let Some((action, binding)) =
+        input_map.apply_pending_rebind(&keys, &mouse_buttons, &gamepads)
+    {
+        rebound.send(ActionRebound { action, binding });
+    }
+
+    action_state.update(&input_map, &keys, &mouse_buttons, &gamepads);
+}
@@ -0,0 +1,466 @@
+use std::{fs, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, KeyboardInput, MouseButton};
+
+use crate::{
+    app::{App, Plugin},
+    engine::{
+        ecs::{
+            event::EventReader,
+            query::Query,
+            resource::{Res, ResMut, Resource},
+            system::{IntoSystemDescriptor, Stage},
+            world::World,
+        },
+        game::{game_loop::FixedTime, game_object::TransformComponent},
+        input::{error::InputFileError, gamepad::GamepadEvent},
+        time::Time,
+        window::{
+            KeyboardInputEvent, MouseButtonInputEvent, MouseMotionEvent, MouseWheelEvent,
+            WindowFocusLost,
+        },
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedInput {
+    Keyboard(KeyboardInput),
+    MouseButton {
+        button: MouseButton,
+        state: ElementState,
+    },
+    MouseMotion {
+        delta: (f64, f64),
+    },
+    MouseWheel {
+        lines: f32,
+    },
+    Gamepad(GamepadEvent),
+    FocusLost,
+}
+
+impl RecordedInput {
+    fn send(&self, world: &World) {
+        match *self {
+            Self::Keyboard(input) => world.send_event(KeyboardInputEvent { input }),
+            Self::MouseButton { button, state } => {
+                world.send_event(MouseButtonInputEvent { button, state })
+            }
+            Self::MouseMotion { delta } => world.send_event(MouseMotionEvent { delta }),
+            Self::MouseWheel { lines } => world.send_event(MouseWheelEvent { lines }),
+            Self::Gamepad(event) => world.send_event(event),
+            Self::FocusLost => world.send_event(WindowFocusLost),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub frame: u64,
+    pub elapsed: Duration,
+    pub delta: Duration,
+    pub fixed_steps: u32,
+    pub checksum: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<RecordedInput>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub fixed_step: Duration,
+    pub max_steps_per_frame: u32,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, InputFileError> {
+        let json = fs::read_to_string(path).map_err(|error| InputFileError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        Self::from_json(&json).map_err(|error| InputFileError::Parse {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), InputFileError> {
+        fs::write(path, self.to_json()).map_err(|error| InputFileError::Io {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delta).sum()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InputRecorder {
+    recording: InputRecording,
+    paused: bool,
+    path: Option<String>,
+}
+
+impl Resource for InputRecorder {}
+
+// flushing on drop keeps the recording however the app shuts down
+impl Drop for InputRecorder {
+    fn drop(&mut self) {
+        if let (Some(path), false) = (&self.path, self.recording.frames.is_empty()) {
+            if let Err(error) = self.recording.save_to_file(path) {
+                println!("{error}");
+            }
+        }
+    }
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_path(path: &str) -> Self {
+        Self {
+            recording: InputRecording::default(),
+            paused: false,
+            path: Some(path.to_string()),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn get_recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    pub fn take_recording(&mut self) -> InputRecording {
+        std::mem::take(&mut self.recording)
+    }
+}
+
+pub struct InputRecordingPlugin {
+    pub path: String,
+}
+
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputRecorder::with_path(&self.path))
+            .add_system(Stage::PostUpdate, record_input.label("record_input"));
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// sums per-transform FNV-1a hashes so the result does not depend on query order, and stays the
+// same across rust releases so old recordings keep replaying
+pub fn get_transform_checksum<'a>(
+    transforms: impl IntoIterator<Item = &'a TransformComponent>,
+) -> u64 {
+    transforms.into_iter().fold(0, |checksum, transform| {
+        let (translation, scale, rotation) =
+            (transform.translation, transform.scale, transform.rotation);
+        let mut hash = FNV_OFFSET_BASIS;
+
+        for value in [
+            translation.x,
+            translation.y,
+            translation.z,
+            scale.x,
+            scale.y,
+            scale.z,
+            rotation.s,
+            rotation.v.x,
+            rotation.v.y,
+            rotation.v.z,
+        ] {
+            for byte in value.to_bits().to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        checksum.wrapping_add(hash)
+    })
+}
+
+type RecordedEventReaders<'w, 's> = (
+    EventReader<'w, 's, KeyboardInputEvent>,
+    EventReader<'w, 's, MouseButtonInputEvent>,
+    EventReader<'w, 's, MouseMotionEvent>,
+    EventReader<'w, 's, MouseWheelEvent>,
+    EventReader<'w, 's, GamepadEvent>,
+    EventReader<'w, 's, WindowFocusLost>,
+);
+
+pub fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    time: Res<Time>,
+    fixed_time: Res<FixedTime>,
+    transforms: Query<&TransformComponent>,
+    readers: RecordedEventReaders,
+) {
+    let (
+        mut keyboard,
+        mut mouse_buttons,
+        mut mouse_motion,
+        mut mouse_wheel,
+        mut gamepad,
+        mut focus_lost,
+    ) = readers;
+
+    let events = keyboard
+        .read()
+        .map(|event| RecordedInput::Keyboard(event.input))
+        .chain(
+            mouse_buttons
+                .read()
+                .map(|event| RecordedInput::MouseButton {
+                    button: event.button,
+                    state: event.state,
+                }),
+        )
+        .chain(
+            mouse_motion
+                .read()
+                .map(|event| RecordedInput::MouseMotion { delta: event.delta }),
+        )
+        .chain(
+            mouse_wheel
+                .read()
+                .map(|event| RecordedInput::MouseWheel { lines: event.lines }),
+        )
+        .chain(gamepad.read().map(|event| RecordedInput::Gamepad(*event)))
+        .chain(focus_lost.read().map(|_| RecordedInput::FocusLost))
+        .collect::<Vec<_>>();
+
+    if recorder.paused {
+        return;
+    }
+
+    let recording = &mut recorder.recording;
+    recording.fixed_step = fixed_time.get_step();
+    recording.max_steps_per_frame = fixed_time.get_max_steps_per_frame();
+    recording.frames.push(RecordedFrame {
+        frame: time.get_frame_count(),
+        elapsed: time.get_elapsed(),
+        delta: time.get_delta(),
+        fixed_steps: fixed_time.get_steps_this_frame(),
+        checksum: get_transform_checksum(transforms.iter()),
+        events,
+    });
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub frames: usize,
+    pub desynced_frames: usize,
+}
+
+pub fn replay_input(app: &mut App, recording: &InputRecording) -> ReplayReport {
+    {
+        let world = app.get_world_mut();
+        world.init_resource::<FixedTime>();
+
+        let mut fixed_time = world.resource_mut::<FixedTime>();
+        fixed_time.set_step(recording.fixed_step);
+        fixed_time.set_max_steps_per_frame(recording.max_steps_per_frame);
+    }
+
+    let mut report = ReplayReport::default();
+
+    for frame in &recording.frames {
+        for event in &frame.events {
+            event.send(app.get_world());
+        }
+
+        app.update_with_delta(frame.delta);
+
+        let world = app.get_world();
+        let checksum = get_transform_checksum(world.query::<&TransformComponent>().iter());
+
+        report.frames += 1;
+        if world.resource::<FixedTime>().get_steps_this_frame() != frame.fixed_steps
+            || checksum != frame.checksum
+        {
+            report.desynced_frames += 1;
+        }
+    }
+
+    report
+}
+
+pub struct InputReplayPlugin {
+    pub path: String,
+}
+
+impl Plugin for InputReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let recording = InputRecording::load_from_file(&self.path);
+
+        app.add_event::<KeyboardInputEvent>()
+            .add_event::<MouseButtonInputEvent>()
+            .add_event::<MouseMotionEvent>()
+            .add_event::<MouseWheelEvent>()
+            .add_event::<GamepadEvent>()
+            .add_event::<WindowFocusLost>()
+            .set_runner(move |mut app| match &recording {
+                Ok(recording) => {
+                    let report = replay_input(&mut app, recording);
+                    println!(
+                        "Replayed {} frames, {} desynced.",
+                        report.frames, report.desynced_frames
+                    );
+                }
+                Err(error) => println!("{error}"),
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::{ModifiersState, VirtualKeyCode};
+
+    use super::*;
+    use crate::{
+        app::CorePlugin,
+        engine::{
+            ecs::entity::Entity,
+            input::{button::ButtonInput, plugin::InputPlugin},
+        },
+    };
+
+    fn walk(
+        keys: Res<ButtonInput<VirtualKeyCode>>,
+        fixed_time: Res<FixedTime>,
+        mut query: Query<&mut TransformComponent>,
+    ) {
+        if keys.is_pressed(VirtualKeyCode::W) {
+            for mut transform in query.iter_mut() {
+                transform.translation.z += fixed_time.get_step_seconds();
+            }
+        }
+    }
+
+    fn build_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(InputPlugin::default())
+            .add_system(Stage::FixedUpdate, walk);
+
+        let player = app.get_world_mut().spawn(TransformComponent::default());
+        (app, player)
+    }
+
+    #[allow(deprecated)]
+    fn key(key: VirtualKeyCode, state: ElementState) -> KeyboardInputEvent {
+        KeyboardInputEvent {
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(key),
+                modifiers: ModifiersState::empty(),
+            },
+        }
+    }
+
+    fn record() -> (InputRecording, TransformComponent) {
+        let (mut app, player) = build_app();
+        app.insert_resource(InputRecorder::new())
+            .add_system(Stage::PostUpdate, record_input);
+
+        for frame in 0..30u64 {
+            match frame {
+                5 => app
+                    .get_world()
+                    .send_event(key(VirtualKeyCode::W, ElementState::Pressed)),
+                20 => app
+                    .get_world()
+                    .send_event(key(VirtualKeyCode::W, ElementState::Released)),
+                _ => {}
+            }
+
+            app.update_with_delta(Duration::from_micros(9000 + frame * 1700));
+        }
+
+        let transform = *app.get_world().get::<TransformComponent>(player).unwrap();
+        let recording = app
+            .get_world_mut()
+            .resource_mut::<InputRecorder>()
+            .take_recording();
+        (recording, transform)
+    }
+
+    #[test]
+    fn replay_matches_recording() {
+        let (recording, recorded) = record();
+        assert_eq!(recording.frames.len(), 30);
+        assert!(recorded.translation.z > 0.0);
+
+        let recording = InputRecording::from_json(&recording.to_json()).unwrap();
+        let (mut app, player) = build_app();
+        let report = replay_input(&mut app, &recording);
+
+        assert_eq!(
+            report,
+            ReplayReport {
+                frames: 30,
+                desynced_frames: 0
+            }
+        );
+        let replayed = app.get_world().get::<TransformComponent>(player).unwrap();
+        assert_eq!(replayed.translation, recorded.translation);
+    }
+
+    #[test]
+    fn replay_detects_diverging_state() {
+        let (mut recording, _) = record();
+        recording.frames[5].events.clear();
+
+        let (mut app, _) = build_app();
+        let report = replay_input(&mut app, &recording);
+
+        // the fixed steps still line up, only the checksum can tell the player never walked
+        assert_eq!(report.frames, 30);
+        assert!(report.desynced_frames > 0);
+    }
+
+    #[test]
+    fn checksum_is_stable() {
+        // pinned so a change to the hash invalidates old recordings loudly
+        let transforms = [TransformComponent::default()];
+        assert_eq!(get_transform_checksum(&transforms), 0xf846_4c67_9520_45a5);
+        assert_eq!(get_transform_checksum(&[]), 0);
+    }
+
+    #[test]
+    fn missing_recording_is_an_error() {
+        assert!(matches!(
+            InputRecording::load_from_file("recordings/missing.json"),
+            Err(InputFileError::Io { .. })
+        ));
+    }
+}
@@ -2,4 +2,8 @@ pub mod rendering;
 pub mod resources;
 pub mod ecs;
 pub mod game;
-pub mod camera;
\ No newline at end of file
+pub mod camera;
+pub mod frustum;
+pub mod time;
+pub mod window;
+pub mod input;
\ No newline at end of file
@@ -9,7 +9,15 @@ use vulkano::{
     render_pass::Framebuffer, descriptor_set::PersistentDescriptorSet,
 };
 
-use crate::{engine::resources::model::StarryModel, vs};
+use crate::engine::{camera::DepthMode, resources::model::StarryModel};
+
+use super::renderer::vs;
+
+pub struct StarryDrawCommand {
+    pub model: StarryModel,
+    pub push_constants: vs::PushConstantData,
+    pub descriptor_set: Arc<PersistentDescriptorSet>,
+}
 
 pub struct StarryCommandBuffer;
 
@@ -99,4 +107,56 @@ impl StarryCommandBuffer {
 
         builder.build().unwrap()
     }
-}
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_scene_command_buffer(
+        command_buffers_allocator: &StandardCommandBufferAllocator,
+        queue_family_index: u32,
+        image_index: u32,
+        frame_buffers: Vec<Arc<Framebuffer>>,
+        viewport: Viewport,
+        pipeline: Arc<GraphicsPipeline>,
+        depth_mode: DepthMode,
+        draws: Vec<StarryDrawCommand>,
+    ) -> PrimaryAutoCommandBuffer {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            command_buffers_allocator,
+            queue_family_index,
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        builder
+            .begin_render_pass(
+                RenderPassBeginInfo {
+                    clear_values: vec![
+                        Some([0.0, 0.0, 0.0, 1.0].into()),
+                        Some(depth_mode.get_clear_depth().into()),
+                    ],
+                    ..RenderPassBeginInfo::framebuffer(frame_buffers[image_index as usize].clone())
+                },
+                SubpassContents::Inline,
+            )
+            .unwrap()
+            .set_viewport(0, [viewport])
+            .bind_pipeline_graphics(pipeline.clone());
+
+        for draw in draws {
+            builder
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    0,
+                    draw.descriptor_set,
+                )
+                .bind_vertex_buffers(0, draw.model.vertex_buffer)
+                .bind_index_buffer(draw.model.index_buffer.clone())
+                .push_constants(pipeline.layout().clone(), 0, draw.push_constants)
+                .draw_indexed(draw.model.index_buffer.len() as u32, 1, 0, 0, 0)
+                .unwrap();
+        }
+
+        builder.end_render_pass().unwrap();
+        builder.build().unwrap()
+    }
+}
\ No newline at end of file
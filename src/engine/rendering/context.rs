@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::allocator::StandardCommandBufferAllocator,
+    descriptor_set::allocator::StandardDescriptorSetAllocator,
+    device::{Device, Queue},
+    memory::allocator::StandardMemoryAllocator,
+};
+
+use crate::engine::ecs::resource::Resource;
+
+#[derive(Clone)]
+pub struct StarryRenderContext {
+    pub device: Arc<Device>,
+    pub queue: Arc<Queue>,
+    pub memory_allocator: Arc<StandardMemoryAllocator>,
+    pub command_buffers_allocator: Arc<StandardCommandBufferAllocator>,
+    pub descriptor_set_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl Resource for StarryRenderContext {}
+
+impl StarryRenderContext {
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>) -> Self {
+        Self {
+            memory_allocator: Arc::new(StandardMemoryAllocator::new_default(device.clone())),
+            command_buffers_allocator: Arc::new(StandardCommandBufferAllocator::new(
+                device.clone(),
+                Default::default(),
+            )),
+            descriptor_set_allocator: Arc::new(StandardDescriptorSetAllocator::new(device.clone())),
+            device,
+            queue,
+        }
+    }
+}
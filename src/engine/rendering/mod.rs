@@ -4,4 +4,6 @@ pub mod shader;
 pub mod surface;
 pub mod device;
 pub mod render_pass;
-pub mod swapchain;
\ No newline at end of file
+pub mod swapchain;
+pub mod context;
+pub mod renderer;
\ No newline at end of file
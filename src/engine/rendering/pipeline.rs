@@ -11,13 +11,13 @@ use vulkano::{
             vertex_input::Vertex,
             viewport::ViewportState, depth_stencil::DepthStencilState,
         },
-        GraphicsPipeline,
+        GraphicsPipeline, StateMode,
     },
     render_pass::{RenderPass, Subpass},
     shader::ShaderModule
 };
 
-use crate::engine::resources::vertex::StarryVertex;
+use crate::engine::{camera::DepthMode, resources::vertex::StarryVertex};
 
 pub struct StarryPipeline;
 
@@ -28,6 +28,26 @@ impl StarryPipeline {
         device: Arc<Device>,
         render_pass: Arc<RenderPass>,
     ) -> Arc<GraphicsPipeline> {
+        Self::create_graphics_pipeline(
+            vertex_shader,
+            fragment_shader,
+            device,
+            render_pass,
+            DepthMode::Standard,
+        )
+    }
+
+    pub fn create_graphics_pipeline(
+        vertex_shader: Arc<ShaderModule>,
+        fragment_shader: Arc<ShaderModule>,
+        device: Arc<Device>,
+        render_pass: Arc<RenderPass>,
+        depth_mode: DepthMode,
+    ) -> Arc<GraphicsPipeline> {
+        let mut depth_stencil_state = DepthStencilState::simple_depth_test();
+        depth_stencil_state.depth.as_mut().unwrap().compare_op =
+            StateMode::Fixed(depth_mode.get_compare_op());
+
         GraphicsPipeline::start()
             .vertex_input_state(StarryVertex::per_vertex())
             .vertex_shader(vertex_shader.entry_point("main").unwrap(), ())
@@ -39,7 +59,7 @@ impl StarryPipeline {
             .multisample_state(MultisampleState::default())
             .color_blend_state(ColorBlendState::default())
             .fragment_shader(fragment_shader.entry_point("main").unwrap(), ())
-            .depth_stencil_state(DepthStencilState::simple_depth_test())
+            .depth_stencil_state(depth_stencil_state)
             .render_pass(Subpass::from(render_pass, 0).unwrap())
             .build(device.clone())
             .unwrap()
@@ -0,0 +1,461 @@
+use std::{collections::HashMap, sync::Arc};
+
+use vulkano::{
+    command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBufferAbstract},
+    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+    device::DeviceOwned,
+    format::Format,
+    image::{view::ImageView, AttachmentImage, ImageAccess, SwapchainImage},
+    memory::allocator::StandardMemoryAllocator,
+    pipeline::{graphics::viewport::Viewport, GraphicsPipeline, Pipeline},
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass},
+    sampler::Sampler,
+    shader::ShaderModule,
+    swapchain::{
+        acquire_next_image, AcquireError, Surface, Swapchain, SwapchainCreationError,
+        SwapchainPresentInfo,
+    },
+    sync::{self, FlushError, GpuFuture},
+};
+
+use crate::{
+    app::{App, Plugin},
+    engine::{
+        camera::{DepthMode, StarryCamera},
+        ecs::{
+            event::EventReader,
+            query::Query,
+            resource::{Res, ResMut, Resource},
+            system::Stage,
+        },
+        frustum::Frustum,
+        game::global_transform::GlobalTransform,
+        resources::{model::StarryModel, scene::TextureAsset, texture::StarryTexture},
+        window::{StarryWindow, WindowResized},
+    },
+};
+
+use super::{
+    command_buffer::{StarryCommandBuffer, StarryDrawCommand},
+    context::StarryRenderContext,
+    device::StarryDevice,
+    pipeline::StarryPipeline,
+    render_pass::StarryRenderPass,
+    surface::StarrySurface,
+    swapchain::StarrySwapchain,
+};
+
+crate::create_shader!("vertex", "assets/shaders/shader.vert", vs);
+crate::create_shader!("fragment", "assets/shaders/shader.frag", fs);
+
+pub struct StarryRenderer {
+    surface: Arc<Surface>,
+    swapchain: Arc<Swapchain>,
+    render_pass: Arc<RenderPass>,
+    pipeline: Arc<GraphicsPipeline>,
+    frame_buffers: Vec<Arc<Framebuffer>>,
+    viewport: Viewport,
+    vertex_shader: Arc<ShaderModule>,
+    fragment_shader: Arc<ShaderModule>,
+    sampler: Arc<Sampler>,
+    textures: HashMap<String, Arc<PersistentDescriptorSet>>,
+    previous_frame_end: Option<Box<dyn GpuFuture + Send + Sync>>,
+    recreate_swapchain: bool,
+    depth_mode: DepthMode,
+}
+
+impl Resource for StarryRenderer {}
+
+impl StarryRenderer {
+    pub fn new(render_context: &StarryRenderContext, surface: Arc<Surface>) -> Self {
+        let device = render_context.device.clone();
+        let (swapchain, images) =
+            StarrySwapchain::create_swapchain_and_images(device.clone(), surface.clone());
+
+        let vertex_shader = vs::load(device.clone()).unwrap();
+        let fragment_shader = fs::load(device.clone()).unwrap();
+
+        let render_pass =
+            StarryRenderPass::create_single_pass_render_pass(device.clone(), swapchain.clone());
+
+        let mut viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [0.0, 0.0],
+            depth_range: 0.0..1.0,
+        };
+
+        let (pipeline, frame_buffers) = create_frame_buffers_and_pipeline(
+            vertex_shader.clone(),
+            fragment_shader.clone(),
+            &render_context.memory_allocator,
+            &images,
+            render_pass.clone(),
+            &mut viewport,
+            DepthMode::Standard,
+        );
+
+        Self {
+            surface,
+            swapchain,
+            render_pass,
+            pipeline,
+            frame_buffers,
+            viewport,
+            vertex_shader,
+            fragment_shader,
+            sampler: StarryTexture::create_default_sampler(device.clone()),
+            textures: HashMap::new(),
+            previous_frame_end: Some(sync::now(device).boxed_send_sync()),
+            recreate_swapchain: false,
+            depth_mode: DepthMode::Standard,
+        }
+    }
+
+    pub fn get_swapchain(&self) -> Arc<Swapchain> {
+        self.swapchain.clone()
+    }
+
+    pub fn get_pipeline(&self) -> Arc<GraphicsPipeline> {
+        self.pipeline.clone()
+    }
+
+    pub fn get_viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn get_depth_mode(&self) -> DepthMode {
+        self.depth_mode
+    }
+
+    pub fn set_depth_mode(&mut self, render_context: &StarryRenderContext, depth_mode: DepthMode) {
+        if self.depth_mode == depth_mode {
+            return;
+        }
+
+        self.depth_mode = depth_mode;
+        self.pipeline = StarryPipeline::create_graphics_pipeline(
+            self.vertex_shader.clone(),
+            self.fragment_shader.clone(),
+            render_context.device.clone(),
+            self.render_pass.clone(),
+            depth_mode,
+        );
+    }
+
+    pub fn request_swapchain_recreation(&mut self) {
+        self.recreate_swapchain = true;
+    }
+
+    pub fn get_texture(
+        &mut self,
+        render_context: &StarryRenderContext,
+        path: &str,
+    ) -> Arc<PersistentDescriptorSet> {
+        if let Some(set) = self.textures.get(path) {
+            return set.clone();
+        }
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &*render_context.command_buffers_allocator,
+            render_context.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        let texture =
+            StarryTexture::create_texture(path, &*render_context.memory_allocator, &mut builder);
+
+        let upload = builder
+            .build()
+            .unwrap()
+            .execute(render_context.queue.clone())
+            .unwrap();
+
+        self.previous_frame_end = Some(match self.previous_frame_end.take() {
+            Some(previous_frame_end) => previous_frame_end.join(upload).boxed_send_sync(),
+            None => upload.boxed_send_sync(),
+        });
+
+        let layout = self.pipeline.layout().set_layouts()[0].clone();
+        let set = PersistentDescriptorSet::new(
+            &*render_context.descriptor_set_allocator,
+            layout,
+            [WriteDescriptorSet::image_view_sampler(
+                0,
+                texture,
+                self.sampler.clone(),
+            )],
+        )
+        .unwrap();
+
+        self.textures.insert(path.to_string(), set.clone());
+        set
+    }
+
+    pub fn render(&mut self, render_context: &StarryRenderContext, draws: Vec<StarryDrawCommand>) {
+        let window_dimensions = StarrySurface::get_extent(self.surface.clone());
+
+        if window_dimensions.height == 0 || window_dimensions.width == 0 {
+            return;
+        }
+
+        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+
+        if self.recreate_swapchain {
+            let (new_swapchain, new_images) = match StarrySwapchain::recreate_swapchain_and_images(
+                self.swapchain.clone(),
+                window_dimensions,
+                render_context.device.clone(),
+                self.surface.clone(),
+            ) {
+                Ok(r) => r,
+                Err(SwapchainCreationError::ImageExtentNotSupported { .. }) => return,
+                Err(e) => panic!("{e}"),
+            };
+
+            self.swapchain = new_swapchain;
+
+            let (new_pipeline, new_frame_buffers) = create_frame_buffers_and_pipeline(
+                self.vertex_shader.clone(),
+                self.fragment_shader.clone(),
+                &render_context.memory_allocator,
+                &new_images,
+                self.render_pass.clone(),
+                &mut self.viewport,
+                self.depth_mode,
+            );
+
+            self.pipeline = new_pipeline;
+            self.frame_buffers = new_frame_buffers;
+            self.recreate_swapchain = false;
+        }
+
+        let (image_index, suboptimal, acquire_future) =
+            match acquire_next_image(self.swapchain.clone(), None) {
+                Ok(r) => r,
+                Err(AcquireError::OutOfDate) => {
+                    self.recreate_swapchain = true;
+                    return;
+                }
+                Err(e) => panic!("Failed to acquire next image: {e}"),
+            };
+
+        if suboptimal {
+            self.recreate_swapchain = true;
+        }
+
+        let command_buffer = StarryCommandBuffer::create_scene_command_buffer(
+            &render_context.command_buffers_allocator,
+            render_context.queue.queue_family_index(),
+            image_index,
+            self.frame_buffers.clone(),
+            self.viewport.clone(),
+            self.pipeline.clone(),
+            self.depth_mode,
+            draws,
+        );
+
+        let future = self
+            .previous_frame_end
+            .take()
+            .unwrap()
+            .join(acquire_future)
+            .then_execute(render_context.queue.clone(), command_buffer)
+            .unwrap()
+            .then_swapchain_present(
+                render_context.queue.clone(),
+                SwapchainPresentInfo::swapchain_image_index(self.swapchain.clone(), image_index),
+            )
+            .then_signal_fence_and_flush();
+
+        self.previous_frame_end = Some(match future {
+            Ok(future) => future.boxed_send_sync(),
+            Err(FlushError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                sync::now(render_context.device.clone()).boxed_send_sync()
+            }
+            Err(e) => {
+                println!("failed to flush future: {e}");
+                sync::now(render_context.device.clone()).boxed_send_sync()
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CullingStats {
+    enabled: bool,
+    visible: usize,
+    culled: usize,
+}
+
+impl Resource for CullingStats {}
+
+impl Default for CullingStats {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            visible: 0,
+            culled: 0,
+        }
+    }
+}
+
+impl CullingStats {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn get_visible(&self) -> usize {
+        self.visible
+    }
+
+    pub fn get_culled(&self) -> usize {
+        self.culled
+    }
+
+    pub fn get_total(&self) -> usize {
+        self.visible + self.culled
+    }
+
+    pub fn reset(&mut self) {
+        self.visible = 0;
+        self.culled = 0;
+    }
+
+    pub fn record(&mut self, visible: bool) {
+        if visible {
+            self.visible += 1;
+        } else {
+            self.culled += 1;
+        }
+    }
+}
+
+pub fn is_model_visible(
+    frustum: &Frustum,
+    model: &StarryModel,
+    transform: &GlobalTransform,
+) -> bool {
+    let bounds = model.get_bounds();
+    let matrix = transform.get_matrix();
+
+    // the sphere is cheap to move and test, so it rejects most objects before the box is touched
+    frustum.intersects_sphere(&bounds.get_bounding_sphere().transform(&matrix))
+        && frustum.intersects_aabb(&bounds.transform(&matrix))
+}
+
+pub struct RenderPlugin;
+
+impl Plugin for RenderPlugin {
+    fn build(&self, app: &mut App) {
+        let (instance, surface) = {
+            let window = app
+                .get_world()
+                .get_resource::<StarryWindow>()
+                .expect("RenderPlugin requires WindowPlugin to be added first.");
+
+            (window.get_instance(), window.get_surface())
+        };
+
+        let (device, mut queues) =
+            StarryDevice::create_device_and_queues(instance, surface.clone());
+        let queue = queues.next().expect("No queues available.");
+        let render_context = StarryRenderContext::new(device, queue);
+        let renderer = StarryRenderer::new(&render_context, surface);
+
+        if !app.get_world().contains_resource::<StarryCamera>() {
+            app.insert_resource(StarryCamera::new());
+        }
+
+        app.insert_resource(render_context)
+            .insert_resource(renderer)
+            .init_resource::<CullingStats>()
+            .register_resource::<StarryCamera>("camera")
+            .add_system(Stage::Render, render_frame);
+    }
+}
+
+pub fn render_frame(
+    mut renderer: ResMut<StarryRenderer>,
+    render_context: Res<StarryRenderContext>,
+    camera: Res<StarryCamera>,
+    mut culling: ResMut<CullingStats>,
+    mut resized: EventReader<WindowResized>,
+    models: Query<(&StarryModel, &GlobalTransform, &TextureAsset)>,
+) {
+    if resized.read().next().is_some() {
+        renderer.request_swapchain_recreation();
+    }
+
+    renderer.set_depth_mode(&render_context, camera.get_depth_mode());
+
+    let projection_view = camera.get_projection_matrix() * camera.get_view_matrix();
+    let frustum = camera.get_frustum();
+    culling.reset();
+
+    let draws = models
+        .iter()
+        .filter(|(model, transform, _)| {
+            let visible = !culling.is_enabled() || is_model_visible(&frustum, model, transform);
+            culling.record(visible);
+            visible
+        })
+        .map(|(model, transform, texture)| StarryDrawCommand {
+            model: model.clone(),
+            push_constants: vs::PushConstantData {
+                transform: (projection_view * transform.get_matrix()).into(),
+            },
+            descriptor_set: renderer.get_texture(&render_context, &texture.path),
+        })
+        .collect::<Vec<_>>();
+
+    renderer.render(&render_context, draws);
+}
+
+fn create_frame_buffers_and_pipeline(
+    vs: Arc<ShaderModule>,
+    fs: Arc<ShaderModule>,
+    memory_allocator: &StandardMemoryAllocator,
+    images: &[Arc<SwapchainImage>],
+    render_pass: Arc<RenderPass>,
+    viewport: &mut Viewport,
+    depth_mode: DepthMode,
+) -> (Arc<GraphicsPipeline>, Vec<Arc<Framebuffer>>) {
+    let dimensions = images[0].dimensions().width_height();
+    viewport.dimensions = [dimensions[0] as f32, dimensions[1] as f32];
+
+    let depth_buffer = ImageView::new_default(
+        AttachmentImage::transient(memory_allocator, dimensions, Format::D32_SFLOAT).unwrap(),
+    )
+    .unwrap();
+
+    let frame_buffers = images
+        .iter()
+        .map(|image| {
+            let view = ImageView::new_default(image.clone()).unwrap();
+            Framebuffer::new(
+                render_pass.clone(),
+                FramebufferCreateInfo {
+                    attachments: vec![view, depth_buffer.clone()],
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let pipeline = StarryPipeline::create_graphics_pipeline(
+        vs,
+        fs,
+        memory_allocator.device().clone(),
+        render_pass,
+        depth_mode,
+    );
+
+    (pipeline, frame_buffers)
+}
@@ -5,7 +5,7 @@ use vulkano_win::VkSurfaceBuild;
 use winit::{
     dpi::{LogicalSize, PhysicalSize},
     event_loop::EventLoopWindowTarget,
-    window::{Window, WindowBuilder},
+    window::{CursorGrabMode, Window, WindowBuilder},
 };
 
 pub struct StarrySurface;
@@ -38,6 +38,23 @@ impl StarrySurface {
     //     surface.object().unwrap().downcast_ref::<&'static Window>().expect("Cannot get window.")
     // }
 
+    pub fn set_cursor_grab(surface: Arc<Surface>, grabbed: bool) {
+        let window = surface.object().unwrap().downcast_ref::<Window>().unwrap();
+
+        if grabbed {
+            window
+                .set_cursor_grab(CursorGrabMode::Confined)
+                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Locked))
+                .unwrap_or_else(|error| println!("Failed to grab cursor: {error}"));
+        } else {
+            window
+                .set_cursor_grab(CursorGrabMode::None)
+                .unwrap_or_else(|error| println!("Failed to release cursor: {error}"));
+        }
+
+        window.set_cursor_visible(!grabbed);
+    }
+
     pub fn get_extent(surface: Arc<Surface>) -> PhysicalSize<u32> {
         surface
             .object()
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use crate::{
+    app::{App, Plugin},
+    engine::{
+        ecs::{
+            command::Commands,
+            entity::Entity,
+            query::{Query, Without},
+            resource::Res,
+            system::{Local, Stage},
+        },
+        rendering::context::StarryRenderContext,
+    },
+};
+
+use super::{model::StarryModel, prefab::PrefabLibrary, scene::ModelAsset};
+
+pub struct AssetPlugin;
+
+impl Plugin for AssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PrefabLibrary>()
+            .add_system(Stage::PreUpdate, load_pending_models);
+    }
+}
+
+pub fn load_pending_models(
+    mut commands: Commands,
+    pending: Query<(Entity, &ModelAsset), Without<StarryModel>>,
+    render_context: Option<Res<StarryRenderContext>>,
+    mut loaded: Local<HashMap<String, StarryModel>>,
+) {
+    let render_context = match render_context {
+        Some(render_context) => render_context,
+        None => return,
+    };
+
+    for (entity, model) in pending.iter() {
+        let model = loaded
+            .entry(model.path.clone())
+            .or_insert_with_key(|path| {
+                StarryModel::create_model_from_file(
+                    path,
+                    &render_context.memory_allocator,
+                    &render_context.command_buffers_allocator,
+                    render_context.queue.clone(),
+                )
+            })
+            .clone();
+
+        commands.entity(entity).insert(model);
+    }
+}
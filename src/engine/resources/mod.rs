@@ -1,3 +1,6 @@
 pub mod vertex;
 pub mod model;
-pub mod texture;
\ No newline at end of file
+pub mod texture;
+pub mod scene;
+pub mod prefab;
+pub mod assets;
\ No newline at end of file
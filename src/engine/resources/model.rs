@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fs::File, io::BufReader, sync::Arc};
 
-use cgmath::{Vector2, Vector3};
+use cgmath::{Vector2, Vector3, Zero};
 use tobj::LoadOptions;
 use vulkano::{
     buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
@@ -13,14 +13,19 @@ use vulkano::{
     sync::GpuFuture,
 };
 
+use crate::engine::{ecs::component::Component, frustum::Aabb};
+
 use super::vertex::StarryVertex;
 
 #[derive(Clone /*, Copy*/)]
 pub struct StarryModel {
     pub vertex_buffer: Subbuffer<[StarryVertex]>,
     pub index_buffer: Subbuffer<[u32]>,
+    bounds: Aabb,
 }
 
+impl Component for StarryModel {}
+
 impl StarryModel {
     pub fn new(
         vertices: Box<Vec<StarryVertex>>,
@@ -29,6 +34,9 @@ impl StarryModel {
         command_buffers_allocator: &StandardCommandBufferAllocator,
         queue: Arc<Queue>,
     ) -> Self {
+        let bounds = Aabb::from_points(vertices.iter().map(|vertex| vertex.position.into()))
+            .unwrap_or_else(|| Aabb::new(Vector3::zero(), Vector3::zero()));
+
         let vertex_buffer = Self::create_vertex_buffer(
             memory_allocator,
             vertices.clone(),
@@ -44,9 +52,14 @@ impl StarryModel {
         Self {
             vertex_buffer,
             index_buffer,
+            bounds,
         }
     }
 
+    pub fn get_bounds(&self) -> Aabb {
+        self.bounds
+    }
+
     pub fn create_vertex_buffer(
         memory_allocator: &(impl MemoryAllocator + ?Sized),
         vertices: Box<Vec<StarryVertex>>,
@@ -0,0 +1,865 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::ecs::{
+    component::Component,
+    entity::Entity,
+    hierarchy::Children,
+    reflect::{set_component_path, ReflectValue, TypeRegistry},
+    resource::Resource,
+    world::World,
+};
+
+use super::{
+    model::StarryModel,
+    scene::{
+        apply_component_json, apply_json, apply_scene_entity, attach_scene_parents,
+        reflect_to_json, ModelAsset, SceneEntity, SceneError, TextureAsset,
+    },
+};
+
+// member id -> component path -> value
+pub type PrefabOverrides = BTreeMap<String, BTreeMap<String, Value>>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScenePrefab {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: PrefabOverrides,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StarryPrefab {
+    pub entities: Vec<SceneEntity>,
+}
+
+impl StarryPrefab {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, SceneError> {
+        let json = fs::read_to_string(path).map_err(|error| SceneError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        Self::from_json(&json).map_err(|error| SceneError::Parse {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), SceneError> {
+        fs::write(path, self.to_json()).map_err(|error| SceneError::Io {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn get_member_ids(&self, path: &str) -> Result<Vec<String>, SceneError> {
+        let mut seen = HashSet::new();
+
+        self.entities
+            .iter()
+            .enumerate()
+            .map(|(index, scene_entity)| {
+                let id = scene_entity.id.clone().ok_or_else(|| {
+                    invalid_prefab(path, format!("entity {index} is missing an id"))
+                })?;
+
+                if !seen.insert(id.clone()) {
+                    return Err(invalid_prefab(
+                        path,
+                        format!("member id {id} is not unique"),
+                    ));
+                }
+
+                Ok(id)
+            })
+            .collect()
+    }
+
+    // checks everything instantiation could trip over without touching the world
+    pub fn validate(&self, world: &World, path: &str) -> Result<Vec<String>, SceneError> {
+        match self.entities.first() {
+            Some(root_entity) if root_entity.parent.is_none() => {}
+            Some(_) => return Err(invalid_prefab(path, "root entity cannot have a parent")),
+            None => return Err(invalid_prefab(path, "does not contain any entities")),
+        }
+
+        for scene_entity in &self.entities {
+            if scene_entity
+                .parent
+                .is_some_and(|parent| parent >= self.entities.len())
+            {
+                return Err(SceneError::InvalidParent(scene_entity.parent.unwrap()));
+            }
+
+            for name in scene_entity.components.keys() {
+                get_prefab_component(world, scene_entity, name)?;
+            }
+        }
+
+        self.get_member_ids(path)
+    }
+
+    pub fn get_member_index(&self, id: &str) -> Option<usize> {
+        self.entities
+            .iter()
+            .position(|scene_entity| scene_entity.id.as_deref() == Some(id))
+    }
+
+    pub fn instantiate(&self, world: &mut World, path: &str) -> Result<Entity, SceneError> {
+        let root = world.spawn_empty();
+
+        match self.instantiate_into(world, path, root, &PrefabOverrides::new()) {
+            Ok(_) => Ok(root),
+            Err(error) => {
+                world.despawn(root);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn instantiate_into(
+        &self,
+        world: &mut World,
+        path: &str,
+        root: Entity,
+        overrides: &PrefabOverrides,
+    ) -> Result<Vec<Entity>, SceneError> {
+        let member_ids = self.validate(world, path)?;
+        if let Some(id) = overrides.keys().find(|id| !member_ids.contains(id)) {
+            return Err(invalid_prefab(
+                path,
+                format!("does not contain overridden member {id}"),
+            ));
+        }
+
+        let members = std::iter::once(root)
+            .chain(self.entities[1..].iter().map(|_| world.spawn_empty()))
+            .collect::<Vec<_>>();
+
+        if let Err(error) = self.apply_members(world, &members, &member_ids, overrides) {
+            for member in &members[1..] {
+                world.despawn_recursive(*member);
+            }
+
+            return Err(error);
+        }
+
+        world.insert(
+            root,
+            PrefabInstance {
+                path: path.to_string(),
+                member_ids,
+                members: members.clone(),
+                overrides: overrides.clone(),
+                root_source: self.entities[0].clone(),
+            },
+        );
+
+        Ok(members)
+    }
+
+    fn apply_members(
+        &self,
+        world: &mut World,
+        members: &[Entity],
+        member_ids: &[String],
+        overrides: &PrefabOverrides,
+    ) -> Result<(), SceneError> {
+        for (scene_entity, entity) in self.entities.iter().zip(members) {
+            apply_scene_entity(world, scene_entity, *entity)?;
+        }
+
+        attach_scene_parents(world, &self.entities, members)?;
+
+        for (id, paths) in overrides {
+            let member = members[member_ids
+                .iter()
+                .position(|member_id| member_id == id)
+                .unwrap()];
+
+            for (field_path, value) in paths {
+                apply_component_json(world, member, field_path, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// overrides are only recorded through set_prefab_override, so runtime changes to members
+// (animation, physics) are never saved into scenes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefabInstance {
+    path: String,
+    member_ids: Vec<String>,
+    members: Vec<Entity>,
+    overrides: PrefabOverrides,
+    // the prefab entry the root was built from, so an update can undo it
+    root_source: SceneEntity,
+}
+
+impl Component for PrefabInstance {}
+
+impl PrefabInstance {
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get_members(&self) -> &[Entity] {
+        &self.members
+    }
+
+    pub fn get_member(&self, id: &str) -> Option<Entity> {
+        self.member_ids
+            .iter()
+            .position(|member_id| member_id == id)
+            .map(|index| self.members[index])
+    }
+
+    pub fn get_member_id(&self, member: Entity) -> Option<&str> {
+        self.members
+            .iter()
+            .position(|entity| *entity == member)
+            .map(|index| self.member_ids[index].as_str())
+    }
+
+    pub fn get_overrides(&self) -> &PrefabOverrides {
+        &self.overrides
+    }
+
+    pub fn is_overridden(&self, id: &str, path: &str) -> bool {
+        self.overrides
+            .get(id)
+            .is_some_and(|paths| paths.contains_key(path))
+    }
+}
+
+#[derive(Default)]
+pub struct PrefabLibrary {
+    prefabs: HashMap<String, Arc<StarryPrefab>>,
+}
+
+impl Resource for PrefabLibrary {}
+
+impl PrefabLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: &str, prefab: StarryPrefab) -> Option<Arc<StarryPrefab>> {
+        self.prefabs.insert(path.to_string(), Arc::new(prefab))
+    }
+
+    pub fn get(&self, path: &str) -> Option<Arc<StarryPrefab>> {
+        self.prefabs.get(path).cloned()
+    }
+
+    pub fn get_or_load(&mut self, path: &str) -> Result<Arc<StarryPrefab>, SceneError> {
+        if let Some(prefab) = self.prefabs.get(path) {
+            return Ok(prefab.clone());
+        }
+
+        let prefab = Arc::new(StarryPrefab::load_from_file(path)?);
+        self.prefabs.insert(path.to_string(), prefab.clone());
+        Ok(prefab)
+    }
+}
+
+pub fn load_prefab(world: &World, path: &str) -> Result<Arc<StarryPrefab>, SceneError> {
+    match world.get_resource_mut::<PrefabLibrary>() {
+        Some(mut library) => library.get_or_load(path),
+        None => Ok(Arc::new(StarryPrefab::load_from_file(path)?)),
+    }
+}
+
+pub fn instantiate_prefab(world: &mut World, path: &str) -> Result<Entity, SceneError> {
+    load_prefab(world, path)?.instantiate(world, path)
+}
+
+pub fn set_prefab_override(
+    world: &mut World,
+    root: Entity,
+    id: &str,
+    path: &str,
+    value: &ReflectValue,
+) -> bool {
+    let member = match get_prefab_member(world, root, id) {
+        Some(member) => member,
+        None => return false,
+    };
+
+    if !set_component_path(world, member, path, value) {
+        return false;
+    }
+
+    let (name, field_path) = path.split_once('.').unwrap_or((path, ""));
+    let registration = world
+        .resource::<TypeRegistry>()
+        .get_component(name)
+        .unwrap()
+        .clone();
+
+    let mut json = Value::Null;
+    registration.with_component(world, member, &mut |component| {
+        component.with_path(field_path, &mut |field| json = reflect_to_json(field));
+    });
+
+    world
+        .get_mut::<PrefabInstance>(root)
+        .unwrap()
+        .overrides
+        .entry(id.to_string())
+        .or_default()
+        .insert(path.to_string(), json);
+    true
+}
+
+pub fn clear_prefab_override(
+    world: &mut World,
+    root: Entity,
+    id: &str,
+    path: &str,
+) -> Result<(), SceneError> {
+    let member = match get_prefab_member(world, root, id) {
+        Some(member) => member,
+        None => return Ok(()),
+    };
+
+    let prefab_path = {
+        let mut instance = world.get_mut::<PrefabInstance>(root).unwrap();
+        if let Some(paths) = instance.overrides.get_mut(id) {
+            paths.remove(path);
+
+            if paths.is_empty() {
+                instance.overrides.remove(id);
+            }
+        }
+
+        instance.path.clone()
+    };
+
+    let prefab = load_prefab(world, &prefab_path)?;
+    let index = prefab.get_member_index(id).ok_or_else(|| {
+        invalid_prefab(
+            &prefab_path,
+            format!("does not contain overridden member {id}"),
+        )
+    })?;
+    let scene_entity = &prefab.entities[index];
+    let (name, field_path) = path.split_once('.').unwrap_or((path, ""));
+
+    let base = get_prefab_component(world, scene_entity, name)?;
+    match get_json_path(&base, field_path) {
+        Some(value) => apply_component_json(world, member, path, value),
+        None => Ok(()),
+    }
+}
+
+// every instance is rebuilt on a staging root first, so a failure leaves the instances and the
+// library as they were
+pub fn update_prefab(
+    world: &mut World,
+    path: &str,
+    prefab: StarryPrefab,
+) -> Result<(), SceneError> {
+    let member_ids = prefab.validate(world, path)?;
+
+    let instances = world
+        .query::<(Entity, &PrefabInstance)>()
+        .iter()
+        .filter(|(_, instance)| instance.path == path)
+        .map(|(root, instance)| (root, instance.clone()))
+        .collect::<Vec<_>>();
+
+    let mut staging_roots = Vec::new();
+    for (_, instance) in &instances {
+        let mut overrides = instance.overrides.clone();
+        overrides.retain(|id, _| {
+            let kept = member_ids.contains(id);
+            if !kept {
+                println!("Prefab {path} no longer contains member {id}, dropping its overrides.");
+            }
+            kept
+        });
+
+        let staging_root = world.spawn_empty();
+        if let Err(error) = prefab.instantiate_into(world, path, staging_root, &overrides) {
+            world.despawn(staging_root);
+            for staging_root in staging_roots {
+                world.despawn_recursive(staging_root);
+            }
+
+            return Err(error);
+        }
+
+        staging_roots.push(staging_root);
+    }
+
+    for ((root, instance), staging_root) in instances.into_iter().zip(staging_roots) {
+        replace_prefab_instance(world, root, &instance, staging_root)?;
+    }
+
+    world.init_resource::<PrefabLibrary>();
+    world.resource_mut::<PrefabLibrary>().insert(path, prefab);
+    Ok(())
+}
+
+fn replace_prefab_instance(
+    world: &mut World,
+    root: Entity,
+    instance: &PrefabInstance,
+    staging_root: Entity,
+) -> Result<(), SceneError> {
+    let mut staged = world.remove::<PrefabInstance>(staging_root).unwrap();
+    let foreign_children = detach_foreign_children(world, instance);
+
+    for member in &instance.members[1..] {
+        world.despawn_recursive(*member);
+    }
+
+    // the staging root already went through the same entry and overrides, so these cannot fail
+    reset_prefab_root(world, root, &instance.root_source, &staged.root_source)?;
+    apply_scene_entity(world, &staged.root_source, root)?;
+    if let Some(paths) = staged.overrides.get(&staged.member_ids[0]) {
+        for (field_path, value) in paths {
+            apply_component_json(world, root, field_path, value)?;
+        }
+    }
+
+    let children = world
+        .get::<Children>(staging_root)
+        .map(|children| children.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    for child in children {
+        world.add_child(root, child);
+    }
+
+    world.despawn(staging_root);
+    staged.members[0] = root;
+
+    for (child, id) in foreign_children {
+        let parent = staged.get_member(&id).unwrap_or(root);
+
+        if world.is_alive(child) {
+            world.add_child(parent, child);
+        }
+    }
+
+    world.insert(root, staged);
+    Ok(())
+}
+
+// undoes what the previous prefab entry applied to the root, so fields, components and models
+// dropped from the prefab do not linger on it
+fn reset_prefab_root(
+    world: &mut World,
+    root: Entity,
+    old: &SceneEntity,
+    new: &SceneEntity,
+) -> Result<(), SceneError> {
+    for name in old.components.keys() {
+        let registration = match world.resource::<TypeRegistry>().get_component(name) {
+            Some(registration) => registration.clone(),
+            None => continue,
+        };
+
+        let default = reflect_to_json(&*registration.create_default());
+        let mut result = Ok(());
+        registration.with_component_mut(world, root, &mut |component| {
+            result = apply_json(component, &default, name);
+        });
+        result?;
+    }
+
+    if old.model.is_some() {
+        world.remove::<ModelAsset>(root);
+
+        if old.model != new.model {
+            world.remove::<StarryModel>(root);
+        }
+    }
+
+    if old.texture.is_some() {
+        world.remove::<TextureAsset>(root);
+    }
+
+    Ok(())
+}
+
+// children attached to members from outside the prefab survive a prefab update
+fn detach_foreign_children(world: &mut World, instance: &PrefabInstance) -> Vec<(Entity, String)> {
+    let mut foreign_children = Vec::new();
+
+    for (member, id) in instance.members.iter().zip(&instance.member_ids).skip(1) {
+        let children = match world.get::<Children>(*member) {
+            Some(children) => children.iter().collect::<Vec<_>>(),
+            None => continue,
+        };
+
+        for child in children {
+            if !instance.members.contains(&child) {
+                world.remove_parent(child);
+                foreign_children.push((child, id.clone()));
+            }
+        }
+    }
+
+    foreign_children
+}
+
+fn get_prefab_member(world: &World, root: Entity, id: &str) -> Option<Entity> {
+    world
+        .get::<PrefabInstance>(root)
+        .and_then(|instance| instance.get_member(id))
+}
+
+fn get_prefab_component(
+    world: &World,
+    scene_entity: &SceneEntity,
+    name: &str,
+) -> Result<Value, SceneError> {
+    let mut component = world
+        .resource::<TypeRegistry>()
+        .get_component(name)
+        .ok_or_else(|| SceneError::UnknownComponent(name.to_string()))?
+        .create_default();
+
+    if let Some(value) = scene_entity.components.get(name) {
+        apply_json(&mut *component, value, name)?;
+    }
+
+    Ok(reflect_to_json(&*component))
+}
+
+fn invalid_prefab(path: &str, reason: impl Into<String>) -> SceneError {
+    SceneError::InvalidPrefab {
+        path: path.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn get_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| current.get(segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        ecs::hierarchy::Parent,
+        game::game_object::TransformComponent,
+        resources::scene::{ModelAsset, StarryScene},
+    };
+
+    const LAMP: &str = r#"{
+        "entities": [
+            {
+                "id": "base",
+                "components": { "transform": { "translation": { "x": 0.0, "y": 1.0, "z": 0.0 } } }
+            },
+            {
+                "id": "bulb",
+                "parent": 0,
+                "model": "bulb.obj",
+                "components": { "transform": { "scale": { "x": 0.25, "y": 0.25, "z": 0.25 } } }
+            }
+        ]
+    }"#;
+
+    const EDITED_LAMP: &str = r#"{
+        "entities": [
+            {
+                "id": "base",
+                "components": { "transform": { "translation": { "x": 0.0, "y": 5.0, "z": 0.0 } } }
+            },
+            {
+                "id": "shade",
+                "parent": 0,
+                "model": "shade.obj"
+            },
+            {
+                "id": "bulb",
+                "parent": 1,
+                "model": "bulb.obj",
+                "components": { "transform": { "scale": { "x": 0.5, "y": 0.5, "z": 0.5 } } }
+            }
+        ]
+    }"#;
+
+    fn create_world() -> World {
+        let mut registry = TypeRegistry::new();
+        registry.register_component::<TransformComponent>("transform");
+
+        let mut library = PrefabLibrary::new();
+        library.insert("lamp", StarryPrefab::from_json(LAMP).unwrap());
+
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world.insert_resource(library);
+        world
+    }
+
+    fn get_member(world: &World, root: Entity, id: &str) -> Entity {
+        world
+            .get::<PrefabInstance>(root)
+            .unwrap()
+            .get_member(id)
+            .unwrap()
+    }
+
+    #[test]
+    fn override_survives_member_insertion() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        assert!(set_prefab_override(
+            &mut world,
+            root,
+            "bulb",
+            "transform.scale.x",
+            &ReflectValue::Float(2.0)
+        ));
+
+        update_prefab(
+            &mut world,
+            "lamp",
+            StarryPrefab::from_json(EDITED_LAMP).unwrap(),
+        )
+        .unwrap();
+
+        let bulb = get_member(&world, root, "bulb");
+        let shade = get_member(&world, root, "shade");
+        assert_eq!(world.get::<ModelAsset>(bulb).unwrap().path, "bulb.obj");
+        assert_eq!(world.get::<TransformComponent>(bulb).unwrap().scale.x, 2.0);
+        assert_eq!(world.get::<TransformComponent>(bulb).unwrap().scale.y, 0.5);
+        assert_eq!(world.get::<ModelAsset>(shade).unwrap().path, "shade.obj");
+        assert_eq!(world.get::<Parent>(bulb).unwrap().get(), shade);
+        assert_eq!(
+            world.get::<TransformComponent>(root).unwrap().translation.y,
+            5.0
+        );
+        assert!(world
+            .get::<PrefabInstance>(root)
+            .unwrap()
+            .is_overridden("bulb", "transform.scale.x"));
+        assert_eq!(world.len(), 3);
+    }
+
+    #[test]
+    fn foreign_children_are_reparented() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        let moth = world.spawn(TransformComponent::default());
+        world.add_child(get_member(&world, root, "bulb"), moth);
+
+        update_prefab(
+            &mut world,
+            "lamp",
+            StarryPrefab::from_json(EDITED_LAMP).unwrap(),
+        )
+        .unwrap();
+
+        assert!(world.is_alive(moth));
+        assert_eq!(
+            world.get::<Parent>(moth).unwrap().get(),
+            get_member(&world, root, "bulb")
+        );
+
+        let mut without_bulb = StarryPrefab::from_json(LAMP).unwrap();
+        without_bulb.entities.truncate(1);
+        update_prefab(&mut world, "lamp", without_bulb).unwrap();
+
+        assert!(world.is_alive(moth));
+        assert_eq!(world.get::<Parent>(moth).unwrap().get(), root);
+    }
+
+    #[test]
+    fn runtime_changes_are_not_overrides() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        let bulb = get_member(&world, root, "bulb");
+
+        world.get_mut::<TransformComponent>(bulb).unwrap().scale.x = 3.0;
+        world
+            .get_mut::<TransformComponent>(root)
+            .unwrap()
+            .translation
+            .x = 4.0;
+        assert!(world
+            .get::<PrefabInstance>(root)
+            .unwrap()
+            .get_overrides()
+            .is_empty());
+
+        assert!(set_prefab_override(
+            &mut world,
+            root,
+            "base",
+            "transform.translation.z",
+            &ReflectValue::Float(2.0)
+        ));
+        assert!(!set_prefab_override(
+            &mut world,
+            root,
+            "missing",
+            "transform.translation.z",
+            &ReflectValue::Float(2.0)
+        ));
+
+        let overrides = world
+            .get::<PrefabInstance>(root)
+            .unwrap()
+            .get_overrides()
+            .clone();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(
+            overrides["base"]["transform.translation.z"],
+            serde_json::json!(2.0)
+        );
+
+        clear_prefab_override(&mut world, root, "base", "transform.translation.z").unwrap();
+        assert_eq!(
+            world.get::<TransformComponent>(root).unwrap().translation.z,
+            0.0
+        );
+        assert!(world
+            .get::<PrefabInstance>(root)
+            .unwrap()
+            .get_overrides()
+            .is_empty());
+    }
+
+    #[test]
+    fn scene_keeps_children_of_members() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        let moth = world.spawn(TransformComponent::default());
+        world.add_child(get_member(&world, root, "bulb"), moth);
+
+        let scene = StarryScene::from_world(&world);
+        assert_eq!(scene.entities.len(), 2);
+        assert_eq!(scene.entities[1].parent, Some(0));
+        assert_eq!(scene.entities[1].parent_member.as_deref(), Some("bulb"));
+
+        let mut reloaded = create_world();
+        let entities = scene.spawn_into(&mut reloaded).unwrap();
+        assert_eq!(
+            reloaded.get::<Parent>(entities[1]).unwrap().get(),
+            get_member(&reloaded, entities[0], "bulb")
+        );
+        assert_eq!(StarryScene::from_world(&reloaded).entities, scene.entities);
+    }
+
+    #[test]
+    fn invalid_update_keeps_instances() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        let bulb = get_member(&world, root, "bulb");
+
+        let mut broken = StarryPrefab::from_json(EDITED_LAMP).unwrap();
+        broken.entities[1].id = None;
+        assert!(matches!(
+            update_prefab(&mut world, "lamp", broken),
+            Err(SceneError::InvalidPrefab { .. })
+        ));
+
+        assert!(world.is_alive(bulb));
+        assert_eq!(get_member(&world, root, "bulb"), bulb);
+        assert!(matches!(
+            instantiate_prefab(&mut world, "missing.json"),
+            Err(SceneError::Io { .. })
+        ));
+        assert_eq!(world.len(), 2);
+    }
+
+    #[test]
+    fn failed_update_keeps_instances_and_library() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        let bulb = get_member(&world, root, "bulb");
+
+        // passes validation but cannot be applied to the new members
+        world
+            .get_mut::<PrefabInstance>(root)
+            .unwrap()
+            .overrides
+            .entry("bulb".to_string())
+            .or_default()
+            .insert("transform.colour".to_string(), Value::from(1.0));
+
+        assert!(update_prefab(
+            &mut world,
+            "lamp",
+            StarryPrefab::from_json(EDITED_LAMP).unwrap()
+        )
+        .is_err());
+
+        assert!(world.is_alive(bulb));
+        assert_eq!(get_member(&world, root, "bulb"), bulb);
+        assert_eq!(world.get::<Parent>(bulb).unwrap().get(), root);
+        assert_eq!(load_prefab(&world, "lamp").unwrap().entities.len(), 2);
+        assert_eq!(world.len(), 2);
+    }
+
+    #[test]
+    fn removed_root_fields_are_reset() {
+        let mut world = create_world();
+        let mut lamp = StarryPrefab::from_json(LAMP).unwrap();
+        lamp.entities[0].model = Some("base.obj".to_string());
+        world.resource_mut::<PrefabLibrary>().insert("lamp", lamp);
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        assert_eq!(world.get::<ModelAsset>(root).unwrap().path, "base.obj");
+
+        let mut bare = StarryPrefab::from_json(LAMP).unwrap();
+        bare.entities[0].components.clear();
+        update_prefab(&mut world, "lamp", bare).unwrap();
+
+        assert_eq!(
+            world.get::<TransformComponent>(root).unwrap().translation.y,
+            0.0
+        );
+        assert!(world.get::<ModelAsset>(root).is_none());
+        assert_eq!(world.len(), 2);
+    }
+
+    #[test]
+    fn clearing_an_override_of_a_removed_member_fails() {
+        let mut world = create_world();
+        let root = instantiate_prefab(&mut world, "lamp").unwrap();
+        assert!(set_prefab_override(
+            &mut world,
+            root,
+            "bulb",
+            "transform.scale.x",
+            &ReflectValue::Float(2.0)
+        ));
+
+        let mut renamed = StarryPrefab::from_json(LAMP).unwrap();
+        renamed.entities[1].id = Some("light".to_string());
+        world
+            .resource_mut::<PrefabLibrary>()
+            .insert("lamp", renamed);
+
+        assert!(matches!(
+            clear_prefab_override(&mut world, root, "bulb", "transform.scale.x"),
+            Err(SceneError::InvalidPrefab { .. })
+        ));
+    }
+}
@@ -0,0 +1,651 @@
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt, fs, io,
+};
+
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use crate::engine::{
+    camera::StarryCamera,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        hierarchy::Parent,
+        reflect::{Reflect, ReflectValue, TypeRegistry},
+        world::World,
+    },
+    game::global_transform::GlobalTransform,
+    rendering::context::StarryRenderContext,
+};
+
+use super::{
+    model::StarryModel,
+    prefab::{load_prefab, PrefabInstance, ScenePrefab},
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io {
+        path: String,
+        error: io::Error,
+    },
+    Parse {
+        path: String,
+        error: serde_json::Error,
+    },
+    UnknownComponent(String),
+    UnknownField(String),
+    InvalidValue {
+        path: String,
+        value: Value,
+    },
+    InvalidParent(usize),
+    UnknownParentMember(String),
+    InvalidPrefab {
+        path: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(formatter, "Failed to access {path}: {error}"),
+            Self::Parse { path, error } => write!(formatter, "Failed to parse {path}: {error}"),
+            Self::UnknownComponent(name) => {
+                write!(formatter, "Scene component {name} is not registered.")
+            }
+            Self::UnknownField(path) => write!(formatter, "Scene field {path} does not exist."),
+            Self::InvalidValue { path, value } => {
+                write!(formatter, "Scene field {path} cannot be set to {value}.")
+            }
+            Self::InvalidParent(index) => {
+                write!(formatter, "Scene parent index {index} is invalid.")
+            }
+            Self::UnknownParentMember(id) => {
+                write!(formatter, "Scene parent member {id} does not exist.")
+            }
+            Self::InvalidPrefab { path, reason } => write!(formatter, "Prefab {path} {reason}."),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Parse { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelAsset {
+    pub path: String,
+}
+
+impl Component for ModelAsset {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureAsset {
+    pub path: String,
+}
+
+impl Component for TextureAsset {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SceneCamera {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    pub up: [f32; 3],
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+    // set when the parent is a member of the prefab instance at `parent`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_member: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefab: Option<ScenePrefab>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StarryScene {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<SceneCamera>,
+    #[serde(default)]
+    pub entities: Vec<SceneEntity>,
+}
+
+impl StarryScene {
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, SceneError> {
+        let json = fs::read_to_string(path).map_err(|error| SceneError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        Self::from_json(&json).map_err(|error| SceneError::Parse {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), SceneError> {
+        fs::write(path, self.to_json()).map_err(|error| SceneError::Io {
+            path: path.to_string(),
+            error,
+        })
+    }
+
+    pub fn from_world(world: &World) -> Self {
+        let prefab_members = world
+            .query::<(Entity, &PrefabInstance)>()
+            .iter()
+            .flat_map(|(root, instance)| {
+                instance.get_members()[1..]
+                    .iter()
+                    .map(|member| {
+                        let id = instance.get_member_id(*member).unwrap().to_string();
+                        (*member, (root, id))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<HashMap<_, _>>();
+
+        let entities = world
+            .get_entities()
+            .iter()
+            .filter(|entity| !prefab_members.contains_key(entity))
+            .collect::<Vec<_>>();
+        let indices = entities
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| (*entity, index))
+            .collect::<HashMap<_, _>>();
+
+        let entities = entities
+            .iter()
+            .map(|entity| {
+                let (parent, parent_member) = match world.get::<Parent>(*entity) {
+                    Some(parent) => match prefab_members.get(&parent.get()) {
+                        Some((root, id)) => (Some(indices[root]), Some(id.clone())),
+                        None => (Some(indices[&parent.get()]), None),
+                    },
+
+                    None => (None, None),
+                };
+
+                match world.get::<PrefabInstance>(*entity) {
+                    Some(instance) => SceneEntity {
+                        parent,
+                        parent_member,
+                        prefab: Some(ScenePrefab {
+                            path: instance.get_path().to_string(),
+                            overrides: instance.get_overrides().clone(),
+                        }),
+                        ..Default::default()
+                    },
+
+                    None => SceneEntity {
+                        parent,
+                        parent_member,
+                        prefab: None,
+                        model: world
+                            .get::<ModelAsset>(*entity)
+                            .map(|model| model.path.clone()),
+                        texture: world
+                            .get::<TextureAsset>(*entity)
+                            .map(|texture| texture.path.clone()),
+                        components: get_scene_components(world, *entity),
+                        ..Default::default()
+                    },
+                }
+            })
+            .collect();
+
+        let camera = world.get_resource::<StarryCamera>().map(|camera| {
+            let position = camera.get_position();
+
+            SceneCamera {
+                position: position.into(),
+                target: (position + camera.get_forward()).into(),
+                up: camera.get_up().into(),
+            }
+        });
+
+        Self { camera, entities }
+    }
+
+    // nothing is left in the world when spawning fails
+    pub fn spawn_into(&self, world: &mut World) -> Result<Vec<Entity>, SceneError> {
+        let entities = self
+            .entities
+            .iter()
+            .map(|_| world.spawn_empty())
+            .collect::<Vec<_>>();
+
+        if let Err(error) = self.apply_entities(world, &entities) {
+            for entity in &entities {
+                world.despawn_recursive(*entity);
+            }
+
+            return Err(error);
+        }
+
+        if let Some(scene_camera) = self.camera {
+            if !world.contains_resource::<StarryCamera>() {
+                world.insert_resource(StarryCamera::new());
+            }
+
+            world.resource_mut::<StarryCamera>().set_view_target(
+                Vector3::from(scene_camera.position),
+                Vector3::from(scene_camera.target),
+                Vector3::from(scene_camera.up),
+            );
+        }
+
+        Ok(entities)
+    }
+
+    fn apply_entities(&self, world: &mut World, entities: &[Entity]) -> Result<(), SceneError> {
+        for (scene_entity, entity) in self.entities.iter().zip(entities) {
+            match &scene_entity.prefab {
+                Some(prefab) => {
+                    load_prefab(world, &prefab.path)?.instantiate_into(
+                        world,
+                        &prefab.path,
+                        *entity,
+                        &prefab.overrides,
+                    )?;
+                }
+
+                None => apply_scene_entity(world, scene_entity, *entity)?,
+            }
+        }
+
+        attach_scene_parents(world, &self.entities, entities)
+    }
+}
+
+pub(crate) fn apply_scene_entity(
+    world: &mut World,
+    scene_entity: &SceneEntity,
+    entity: Entity,
+) -> Result<(), SceneError> {
+    for (name, value) in &scene_entity.components {
+        apply_component_json(world, entity, name, value)?;
+    }
+
+    if let Some(path) = &scene_entity.model {
+        world.insert(entity, ModelAsset { path: path.clone() });
+    }
+
+    if let Some(path) = &scene_entity.texture {
+        world.insert(entity, TextureAsset { path: path.clone() });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn attach_scene_parents(
+    world: &mut World,
+    scene_entities: &[SceneEntity],
+    entities: &[Entity],
+) -> Result<(), SceneError> {
+    for (scene_entity, entity) in scene_entities.iter().zip(entities) {
+        if let Some(index) = scene_entity.parent {
+            let mut parent = *entities
+                .get(index)
+                .ok_or(SceneError::InvalidParent(index))?;
+
+            if let Some(id) = &scene_entity.parent_member {
+                parent = world
+                    .get::<PrefabInstance>(parent)
+                    .and_then(|instance| instance.get_member(id))
+                    .ok_or_else(|| SceneError::UnknownParentMember(id.clone()))?;
+            }
+
+            if parent == *entity || world.is_ancestor_of(*entity, parent) {
+                return Err(SceneError::InvalidParent(index));
+            }
+
+            world.add_child(parent, *entity);
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn get_scene_components(world: &World, entity: Entity) -> BTreeMap<String, Value> {
+    let registry = world.resource::<TypeRegistry>();
+    let mut components = BTreeMap::new();
+
+    for registration in registry.get_components() {
+        if registration.get_type_id() == TypeId::of::<GlobalTransform>() {
+            continue;
+        }
+
+        registration.with_component(world, entity, &mut |component| {
+            components.insert(
+                registration.get_name().to_string(),
+                reflect_to_json(component),
+            );
+        });
+    }
+
+    components
+}
+
+pub(crate) fn apply_component_json(
+    world: &mut World,
+    entity: Entity,
+    path: &str,
+    value: &Value,
+) -> Result<(), SceneError> {
+    let (name, field_path) = path.split_once('.').unwrap_or((path, ""));
+    let registration = world
+        .resource::<TypeRegistry>()
+        .get_component(name)
+        .ok_or_else(|| SceneError::UnknownComponent(name.to_string()))?
+        .clone();
+
+    if !registration.has(world, entity) {
+        registration.insert_default(world, entity);
+    }
+
+    let mut result = Err(SceneError::UnknownField(path.to_string()));
+    registration.with_component_mut(world, entity, &mut |component| {
+        component.with_path_mut(field_path, &mut |field| {
+            result = apply_json(field, value, path);
+        });
+    });
+    result
+}
+
+pub fn load_model_assets(world: &mut World) {
+    let render_context = world.resource::<StarryRenderContext>().clone();
+    let pending = world
+        .query::<(Entity, &ModelAsset)>()
+        .iter()
+        .filter(|(entity, _)| !world.has::<StarryModel>(*entity))
+        .map(|(entity, model)| (entity, model.path.clone()))
+        .collect::<Vec<_>>();
+
+    let mut loaded: HashMap<String, StarryModel> = HashMap::new();
+
+    for (entity, path) in pending {
+        let model = loaded
+            .entry(path)
+            .or_insert_with_key(|path| {
+                StarryModel::create_model_from_file(
+                    path,
+                    &render_context.memory_allocator,
+                    &render_context.command_buffers_allocator,
+                    render_context.queue.clone(),
+                )
+            })
+            .clone();
+
+        world.insert(entity, model);
+    }
+}
+
+pub(crate) fn reflect_to_json(reflect: &dyn Reflect) -> Value {
+    match reflect.get_value() {
+        Some(ReflectValue::Bool(value)) => Value::Bool(value),
+        Some(ReflectValue::Int(value)) => Value::from(value),
+        Some(ReflectValue::Float(value)) if reflect.as_any().is::<f32>() => {
+            float_to_json((value as f32).to_string().parse().unwrap())
+        }
+        Some(ReflectValue::Float(value)) => float_to_json(value),
+        Some(ReflectValue::String(value)) => Value::String(value),
+        None => Value::Object(
+            reflect
+                .get_field_names()
+                .into_iter()
+                .map(|name| {
+                    let mut value = Value::Null;
+                    reflect.with_field(name, &mut |field| value = reflect_to_json(field));
+                    (name.to_string(), value)
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn float_to_json(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+pub(crate) fn apply_json(
+    reflect: &mut dyn Reflect,
+    value: &Value,
+    path: &str,
+) -> Result<(), SceneError> {
+    let invalid_value = || SceneError::InvalidValue {
+        path: path.to_string(),
+        value: value.clone(),
+    };
+
+    let reflect_value = match value {
+        Value::Object(fields) => {
+            for (name, field_value) in fields {
+                let field_path = format!("{path}.{name}");
+                let mut result = Err(SceneError::UnknownField(field_path.clone()));
+                reflect.with_field_mut(name, &mut |field| {
+                    result = apply_json(field, field_value, &field_path);
+                });
+                result?;
+            }
+
+            return Ok(());
+        }
+
+        Value::Bool(value) => ReflectValue::Bool(*value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => ReflectValue::Int(value),
+            None => ReflectValue::Float(number.as_f64().ok_or_else(invalid_value)?),
+        },
+        Value::String(value) => ReflectValue::String(value.clone()),
+        Value::Null | Value::Array(_) => return Err(invalid_value()),
+    };
+
+    if !reflect.set_value(&reflect_value) {
+        return Err(invalid_value());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{InnerSpace, Vector3};
+
+    use super::*;
+    use crate::{
+        app::{App, CorePlugin},
+        engine::game::game_object::TransformComponent,
+    };
+
+    const SCENE: &str = r#"{
+        "camera": { "position": [10.0, -10.0, 10.0], "target": [0.0, 0.0, 2.0], "up": [0.0, -1.0, 0.0] },
+        "entities": [
+            {
+                "model": "assets/models/viking_room.obj",
+                "texture": "assets/textures/viking_room.png",
+                "components": {
+                    "transform": {
+                        "translation": { "x": 0.0, "y": 0.0, "z": 2.0 },
+                        "scale": { "x": 0.5, "y": 0.5, "z": 0.5 },
+                        "rotation": { "x": 30.0, "y": 45.0, "z": 10.0 }
+                    }
+                }
+            },
+            {
+                "parent": 0,
+                "components": {
+                    "transform": {
+                        "translation": { "x": 0.1, "y": 1.5, "z": 0.0 },
+                        "scale": { "x": 1.0, "y": 1.0, "z": 1.0 },
+                        "rotation": { "x": 0.0, "y": 45.0, "z": 0.0 }
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    fn create_world() -> World {
+        let mut registry = TypeRegistry::new();
+        registry.register_component::<TransformComponent>("transform");
+        registry.register_component::<GlobalTransform>("global_transform");
+
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let scene = StarryScene::from_json(SCENE).unwrap();
+
+        assert_eq!(StarryScene::from_json(&scene.to_json()).unwrap(), scene);
+    }
+
+    #[test]
+    fn world_round_trip() {
+        let scene = StarryScene::from_json(SCENE).unwrap();
+
+        let mut world = create_world();
+        let entities = scene.spawn_into(&mut world).unwrap();
+        world.insert(entities[1], GlobalTransform::default());
+
+        assert_eq!(
+            world
+                .get::<TransformComponent>(entities[1])
+                .unwrap()
+                .translation
+                .x,
+            0.1
+        );
+        assert_eq!(world.get::<Parent>(entities[1]).unwrap().get(), entities[0]);
+
+        let saved = StarryScene::from_world(&world);
+        assert_eq!(saved.entities, scene.entities);
+
+        let mut reloaded = create_world();
+        saved.spawn_into(&mut reloaded).unwrap();
+        assert_eq!(StarryScene::from_world(&reloaded).entities, saved.entities);
+
+        let camera = world.resource::<StarryCamera>();
+        let reloaded_camera = reloaded.resource::<StarryCamera>();
+        let difference = camera.get_view_matrix() - reloaded_camera.get_view_matrix();
+        assert!(
+            difference.x.magnitude()
+                + difference.y.magnitude()
+                + difference.z.magnitude()
+                + difference.w.magnitude()
+                < 1e-4
+        );
+        assert!((camera.get_position() - Vector3::new(10.0, -10.0, 10.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn despawned_parent_leaves_a_root() {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin);
+
+        let entities = StarryScene::from_json(SCENE)
+            .unwrap()
+            .spawn_into(app.get_world_mut())
+            .unwrap();
+        app.get_world_mut().despawn(entities[0]);
+        app.update();
+
+        let world = app.get_world();
+        assert!(world.get::<Parent>(entities[1]).is_none());
+        assert_eq!(
+            world
+                .get::<GlobalTransform>(entities[1])
+                .unwrap()
+                .get_matrix(),
+            world
+                .get::<TransformComponent>(entities[1])
+                .unwrap()
+                .get_transform_matrix()
+        );
+
+        let saved = StarryScene::from_world(world);
+        assert_eq!(saved.entities.len(), 1);
+        assert_eq!(saved.entities[0].parent, None);
+    }
+
+    #[test]
+    fn load_errors() {
+        assert!(matches!(
+            StarryScene::load_from_file("assets/scenes/missing.json"),
+            Err(SceneError::Io { .. })
+        ));
+        assert!(matches!(
+            StarryScene::default().save_to_file("assets/scenes/missing/scene.json"),
+            Err(SceneError::Io { .. })
+        ));
+
+        let mut world = create_world();
+        let spawn =
+            |world: &mut World, json: &str| StarryScene::from_json(json).unwrap().spawn_into(world);
+
+        assert!(matches!(
+            spawn(&mut world, r#"{ "entities": [{ "components": { "light": {} } }] }"#),
+            Err(SceneError::UnknownComponent(name)) if name == "light"
+        ));
+        assert!(matches!(
+            spawn(
+                &mut world,
+                r#"{ "entities": [{ "components": { "transform": { "skew": 1.0 } } }] }"#
+            ),
+            Err(SceneError::UnknownField(path)) if path == "transform.skew"
+        ));
+        assert!(matches!(
+            spawn(
+                &mut world,
+                r#"{ "entities": [{ "components": { "transform": { "scale": { "x": "big" } } } }] }"#
+            ),
+            Err(SceneError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            spawn(&mut world, r#"{ "entities": [{}, { "parent": 5 }] }"#),
+            Err(SceneError::InvalidParent(5))
+        ));
+        assert!(matches!(
+            spawn(
+                &mut world,
+                r#"{ "entities": [{ "parent": 1 }, { "parent": 0 }] }"#
+            ),
+            Err(SceneError::InvalidParent(_))
+        ));
+        assert_eq!(world.len(), 0);
+    }
+}
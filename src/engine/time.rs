@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use crate::engine::ecs::resource::{ResMut, Resource};
+
+pub struct Time {
+    startup: Instant,
+    last_update: Option<Instant>,
+    delta: Duration,
+    elapsed: Duration,
+    frame_count: u64,
+}
+
+impl Resource for Time {}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self {
+            startup: Instant::now(),
+            last_update: None,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame_count: 0,
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.update_with_instant(Instant::now());
+    }
+
+    pub fn update_with_instant(&mut self, now: Instant) {
+        self.delta = match self.last_update {
+            Some(last_update) => now - last_update,
+            None => Duration::ZERO,
+        };
+        self.elapsed = now - self.startup;
+        self.last_update = Some(now);
+        self.frame_count += 1;
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+        self.last_update = Some(Instant::now());
+        self.frame_count += 1;
+    }
+
+    pub fn get_delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn get_delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn get_elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn get_frame_count(&self) -> u64 {
+        self.frame_count
+    }
+}
+
+pub fn update_time(mut time: ResMut<Time>) {
+    time.update();
+}
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use vulkano::{
+    instance::{Instance, InstanceCreateInfo},
+    swapchain::Surface,
+    VulkanLibrary,
+};
+use winit::{
+    dpi::PhysicalSize,
+    event::{
+        DeviceEvent, ElementState, Event as WinitEvent, KeyboardInput, MouseButton,
+        MouseScrollDelta, WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoop},
+    window::Window,
+};
+
+use crate::{
+    app::{App, Plugin},
+    engine::{
+        ecs::{
+            event::{Event, Events},
+            resource::Resource,
+        },
+        rendering::surface::StarrySurface,
+    },
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyboardInputEvent {
+    pub input: KeyboardInput,
+}
+
+impl Event for KeyboardInputEvent {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MouseButtonInputEvent {
+    pub button: MouseButton,
+    pub state: ElementState,
+}
+
+impl Event for MouseButtonInputEvent {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MouseMotionEvent {
+    pub delta: (f64, f64),
+}
+
+impl Event for MouseMotionEvent {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MouseWheelEvent {
+    pub lines: f32,
+}
+
+impl Event for MouseWheelEvent {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowResized {
+    pub size: PhysicalSize<u32>,
+}
+
+impl Event for WindowResized {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowCloseRequested;
+
+impl Event for WindowCloseRequested {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowFocusLost;
+
+impl Event for WindowFocusLost {}
+
+pub struct StarryWindow {
+    instance: Arc<Instance>,
+    surface: Arc<Surface>,
+}
+
+impl Resource for StarryWindow {}
+
+impl StarryWindow {
+    pub fn new(instance: Arc<Instance>, surface: Arc<Surface>) -> Self {
+        Self { instance, surface }
+    }
+
+    pub fn get_instance(&self) -> Arc<Instance> {
+        self.instance.clone()
+    }
+
+    pub fn get_surface(&self) -> Arc<Surface> {
+        self.surface.clone()
+    }
+
+    pub fn get_window(&self) -> &Window {
+        self.surface
+            .object()
+            .unwrap()
+            .downcast_ref::<Window>()
+            .expect("Surface is not backed by a winit window.")
+    }
+
+    pub fn set_cursor_grab(&self, grabbed: bool) {
+        StarrySurface::set_cursor_grab(self.surface.clone(), grabbed);
+    }
+
+    pub fn get_extent(&self) -> PhysicalSize<u32> {
+        StarrySurface::get_extent(self.surface.clone())
+    }
+
+    pub fn get_aspect(&self) -> f32 {
+        let extent = self.get_extent();
+        extent.width as f32 / extent.height.max(1) as f32
+    }
+}
+
+pub struct WindowPlugin {
+    pub width: i32,
+    pub height: i32,
+    pub title: String,
+}
+
+impl Default for WindowPlugin {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 800,
+            title: "Starry Vulkan Engine".to_string(),
+        }
+    }
+}
+
+impl Plugin for WindowPlugin {
+    fn build(&self, app: &mut App) {
+        let library = VulkanLibrary::new().unwrap();
+        let required_extensions = vulkano_win::required_extensions(&library);
+
+        let instance = Instance::new(
+            library,
+            InstanceCreateInfo {
+                enabled_extensions: required_extensions,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let event_loop = EventLoop::new();
+        let surface = StarrySurface::new(
+            self.width,
+            self.height,
+            &self.title,
+            &event_loop,
+            instance.clone(),
+        );
+
+        app.insert_resource(StarryWindow::new(instance, surface))
+            .add_event::<WindowResized>()
+            .add_event::<WindowCloseRequested>()
+            .set_runner(move |app| run_event_loop(app, event_loop));
+    }
+}
+
+fn run_event_loop(mut app: App, event_loop: EventLoop<()>) -> ! {
+    event_loop.run(move |event, _, control_flow| {
+        let world = app.get_world_mut();
+
+        match event {
+            WinitEvent::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                world.send_event(WindowCloseRequested);
+                *control_flow = ControlFlow::Exit;
+            }
+
+            WinitEvent::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                world.send_event(WindowResized { size });
+            }
+
+            WinitEvent::WindowEvent {
+                event: WindowEvent::Focused(false),
+                ..
+            } if world.contains_resource::<Events<WindowFocusLost>>() => {
+                world.send_event(WindowFocusLost);
+            }
+
+            WinitEvent::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } if world.contains_resource::<Events<KeyboardInputEvent>>() => {
+                world.send_event(KeyboardInputEvent { input });
+            }
+
+            WinitEvent::WindowEvent {
+                event: WindowEvent::MouseInput { button, state, .. },
+                ..
+            } if world.contains_resource::<Events<MouseButtonInputEvent>>() => {
+                world.send_event(MouseButtonInputEvent { button, state });
+            }
+
+            WinitEvent::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                ..
+            } if world.contains_resource::<Events<MouseWheelEvent>>() => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => lines,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 20.0,
+                };
+
+                world.send_event(MouseWheelEvent { lines });
+            }
+
+            WinitEvent::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } if world.contains_resource::<Events<MouseMotionEvent>>() => {
+                world.send_event(MouseMotionEvent { delta });
+            }
+
+            WinitEvent::RedrawEventsCleared => {
+                app.update();
+            }
+
+            // torn down while the loop is alive so resources get to flush on drop
+            WinitEvent::LoopDestroyed => {
+                drop(std::mem::take(&mut app));
+            }
+
+            _ => (),
+        }
+    })
+}